@@ -1,6 +1,13 @@
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
+use evalcraft_core::observer::RESUME_ENV;
+use evalcraft_core::selection::{CASES_ENV, SHARD_ENV, TAGS_ENV};
+use evalcraft_core::{
+    CancellationToken, CaseSelector, EvalBuilder, EvalConfig, JsonlObserver, ProgressObserver, RunStatus,
+    CANCEL_ON_CTRL_C_ENV,
+};
 use evalcraft_store::Store;
 
 #[derive(Parser)]
@@ -22,10 +29,71 @@ enum Commands {
         #[arg(short, long)]
         filter: Option<String>,
 
+        /// Continue a stored run: only missing or errored cases are run. Without a
+        /// run id, each eval continues its most recent run
+        #[arg(long, value_name = "RUN_ID", num_args = 0..=1, default_missing_value = "latest", conflicts_with = "watch")]
+        resume: Option<String>,
+
         /// Path to search for tests (defaults to current directory)
         #[arg(default_value = ".")]
         path: PathBuf,
+
+        #[command(flatten)]
+        select: SelectArgs,
     },
+    /// Run an evaluation described by a YAML config file
+    Eval {
+        /// Path to the eval config (e.g. examples/eval.yaml)
+        config: PathBuf,
+
+        /// Also append each case result to this JSON Lines file as it finishes
+        #[arg(long)]
+        jsonl: Option<PathBuf>,
+
+        #[command(flatten)]
+        select: SelectArgs,
+    },
+}
+
+/// Which cases to run inside each eval
+#[derive(Args)]
+struct SelectArgs {
+    /// Only run cases whose id matches one of these comma-separated globs (e.g. 'capital_*')
+    #[arg(long)]
+    cases: Option<String>,
+
+    /// Only run cases whose tags match an expression (e.g. 'math && !slow')
+    #[arg(long)]
+    tags: Option<String>,
+
+    /// Only run one shard of the cases, as index/total (e.g. 2/8)
+    #[arg(long)]
+    shard: Option<String>,
+}
+
+impl SelectArgs {
+    /// Pass the selection to a child process through the environment
+    fn apply_env(&self, cmd: &mut Command) {
+        for (name, value) in [(CASES_ENV, &self.cases), (TAGS_ENV, &self.tags), (SHARD_ENV, &self.shard)] {
+            if let Some(value) = value {
+                cmd.env(name, value);
+            }
+        }
+    }
+
+    fn selector(&self) -> anyhow::Result<CaseSelector> {
+        let mut selector = CaseSelector::new();
+        if let Some(cases) = &self.cases {
+            selector = selector.ids(cases)?;
+        }
+        if let Some(tags) = &self.tags {
+            selector = selector.tags(tags)?;
+        }
+        if let Some(shard) = &self.shard {
+            selector = selector.shard_spec(shard)?;
+        }
+        Ok(selector)
+    }
 }
 
 #[tokio::main]
@@ -37,16 +105,24 @@ async fn main() -> anyhow::Result<()> {
 
     // Initialize/Ensure the store exists for this run
     // In a real app, we might want to locate this relative to the project root
-    let _store = Store::open("eval_history.db")?;
+    Store::open(DB_PATH)?;
 
     match &cli.command {
-        Some(Commands::Run { watch, filter, path }) => {
+        Some(Commands::Run { watch, filter, resume, path, select }) => {
+            // Fail early on a malformed selection rather than inside every test binary
+            select.selector()?;
+            if let Some(run_id) = resume.as_deref().filter(|r| *r != "latest") {
+                run_id.parse::<i64>().map_err(|_| anyhow::anyhow!("--resume takes a run id, got {:?}", run_id))?;
+            }
             if *watch {
-                run_watch_mode(path, filter.as_deref()).await?;
+                run_watch_mode(path, filter.as_deref(), select).await?;
             } else {
-                run_once(path, filter.as_deref()).await?;
+                run_once(path, filter.as_deref(), resume.as_deref(), select, true).await?;
             }
         }
+        Some(Commands::Eval { config, jsonl, select }) => {
+            run_config(config, jsonl.as_deref(), select).await?;
+        }
         None => {
             use clap::CommandFactory;
             Cli::command().print_help()?;
@@ -56,7 +132,18 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn run_once(_path: &PathBuf, filter: Option<&str>) -> anyhow::Result<()> {
+const DB_PATH: &str = "eval_history.db";
+
+/// Run `evalcraft run`'s `cargo test`. With `forward_ctrl_c`, the first Ctrl-C
+/// asks the evals to stop (they finish in-flight cases and are stored as
+/// interrupted) instead of killing cargo and the test binaries outright.
+async fn run_once(
+    _path: &PathBuf,
+    filter: Option<&str>,
+    resume: Option<&str>,
+    select: &SelectArgs,
+    forward_ctrl_c: bool,
+) -> anyhow::Result<()> {
     // For Approach 1: We assume the user has defined examples/tests in Cargo.toml.
     // We will run `cargo test` (or `cargo run --example`) and let the output stream to stdout.
     // To enable "Implicit Persistence", we set an environment variable `EVALCRAFT_DB_PATH`.
@@ -78,10 +165,10 @@ async fn run_once(_path: &PathBuf, filter: Option<&str>) -> anyhow::Result<()> {
     
     // Only run examples and integration tests, not library unit tests
     // This prevents running the unit tests in evalcraft-core itself
-    cmd.args(&["--examples", "--tests"]);
+    cmd.args(["--examples", "--tests"]);
     
     // Enable the persistence feature in the core library dynamically
-    cmd.args(&["--features", "evalcraft-core/persistence"]);
+    cmd.args(["--features", "evalcraft-core/persistence"]);
     
     // If the user is running specific examples (common for evals), we might default to running all examples
     // or specific ones if filtered.
@@ -92,12 +179,25 @@ async fn run_once(_path: &PathBuf, filter: Option<&str>) -> anyhow::Result<()> {
     }
 
     // Inject the DB path so the child process knows where to save results
-    let db_path = std::env::current_dir()?.join("eval_history.db");
+    let db_path = std::env::current_dir()?.join(DB_PATH);
     cmd.env("EVALCRAFT_DB_PATH", db_path);
+
+    // Case selection is applied by `EvalBuilder::build` in the child process
+    select.apply_env(&mut cmd);
+
+    // So is resuming: each eval skips the cases its stored eval already completed
+    if let Some(resume) = resume {
+        println!("⏯️  Resuming run: {}", resume);
+        cmd.env(RESUME_ENV, resume);
+    }
     
     // We want to capture the status to know if tests passed
     // We let stdout/stderr inherit so the user sees the progress
-    let status = cmd.status()?;
+    let status = if forward_ctrl_c {
+        wait_forwarding_ctrl_c(cmd).await?
+    } else {
+        cmd.status()?
+    };
 
     if status.success() {
         println!("✅ Evaluation run completed successfully.");
@@ -110,15 +210,107 @@ async fn run_once(_path: &PathBuf, filter: Option<&str>) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Run `cmd` in its own process group with SIGINT ignored, so a Ctrl-C in the
+/// terminal doesn't kill cargo. Instead the first Ctrl-C is forwarded to the group,
+/// where only the evals (told by `EVALCRAFT_CANCEL_ON_CTRL_C`) react by cancelling;
+/// the second kills the group.
+#[cfg(unix)]
+async fn wait_forwarding_ctrl_c(mut cmd: Command) -> anyhow::Result<std::process::ExitStatus> {
+    use std::os::unix::process::CommandExt;
+
+    cmd.env(CANCEL_ON_CTRL_C_ENV, "1").process_group(0);
+    // SAFETY: `signal` is async-signal-safe, and the disposition survives `exec`.
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::signal(libc::SIGINT, libc::SIG_IGN);
+            Ok(())
+        });
+    }
+
+    let mut child = tokio::process::Command::from(cmd).spawn()?;
+    let group = child.id().map(|id| id as libc::pid_t);
+    let mut interrupted = false;
+    loop {
+        tokio::select! {
+            status = child.wait() => return Ok(status?),
+            Ok(()) = tokio::signal::ctrl_c() => {
+                let Some(group) = group else { continue };
+                if interrupted {
+                    // SAFETY: plain syscall on the child's process group.
+                    unsafe { libc::killpg(group, libc::SIGKILL) };
+                    std::process::exit(130);
+                }
+                interrupted = true;
+                eprintln!("\n⏹️  Interrupted, finishing in-flight cases (Ctrl-C again to abort)...");
+                // SAFETY: as above.
+                unsafe { libc::killpg(group, libc::SIGINT) };
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_forwarding_ctrl_c(mut cmd: Command) -> anyhow::Result<std::process::ExitStatus> {
+    cmd.env(CANCEL_ON_CTRL_C_ENV, "1");
+    Ok(tokio::process::Command::from(cmd).status().await?)
+}
+
+async fn run_config(path: &Path, jsonl: Option<&Path>, select: &SelectArgs) -> anyhow::Result<()> {
+    let config = EvalConfig::from_file(path)?;
+    let name = config.name.clone().unwrap_or_else(|| "Config Eval".to_string());
+
+    println!("🚀 Running eval '{}' from {:?}...", name, path);
+
+    // First Ctrl-C: stop starting cases and keep what finished. Second: abort.
+    let cancel = CancellationToken::new();
+    let on_interrupt = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("\n⏹️  Interrupted, finishing in-flight cases (Ctrl-C again to abort)...");
+            on_interrupt.cancel();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        }
+    });
+
+    // Cases are saved as they finish, so an aborted run keeps what completed.
+    let mut builder = EvalBuilder::from_config(&config)?
+        .name(name.clone())
+        .persist_to(DB_PATH)
+        .select(select.selector()?)
+        .cancel_token(cancel)
+        .observer(Arc::new(ProgressObserver::new()));
+    if let Some(jsonl) = jsonl {
+        builder = builder.observer(Arc::new(JsonlObserver::create(jsonl)?));
+    }
+    let eval = builder.build()?;
+    let result = eval.run().await?;
+
+    println!("{}", result.summary_table());
+    println!("💾 Saved eval '{}' to {}", name, DB_PATH);
+    match result.status {
+        RunStatus::Interrupted => {
+            println!("⚠️  Run was interrupted; {} case(s) were skipped", result.summary.skipped);
+        }
+        RunStatus::Failed => {
+            anyhow::bail!("Run failed: {}", result.error.as_deref().unwrap_or("unknown error"));
+        }
+        RunStatus::Completed => {}
+    }
+
+    Ok(())
+}
+
 use notify::{RecommendedWatcher, RecursiveMode, Watcher, Event};
 use std::sync::mpsc::channel;
 use std::time::Duration;
 
-async fn run_watch_mode(path: &PathBuf, filter: Option<&str>) -> anyhow::Result<()> {
+async fn run_watch_mode(path: &PathBuf, filter: Option<&str>, select: &SelectArgs) -> anyhow::Result<()> {
     println!("👀 Watching for changes in {:?}...", path);
 
     // Initial run
-    let _ = run_once(path, filter).await;
+    let _ = run_once(path, filter, None, select, false).await;
 
     // Create a channel to receive the events.
     let (tx, rx) = channel();
@@ -136,28 +328,28 @@ async fn run_watch_mode(path: &PathBuf, filter: Option<&str>) -> anyhow::Result<
             Ok(Ok(Event { kind, paths, .. })) => {
                 // Check if it's a relevant file change (e.g. .rs, .toml)
                 let changed_files: Vec<_> = paths.iter()
-                    .filter(|p| p.extension().map_or(false, |ext| ext == "rs" || ext == "toml"))
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "rs" || ext == "toml"))
                     .collect();
 
                 if !changed_files.is_empty() {
                     println!("📝 Change detected ({:?}). Re-running...", kind);
                     
                     // Simple debounce: clear queue of any other pending events for a short duration
-                    while let Ok(_) = rx.recv_timeout(Duration::from_millis(100)) {}
+                    while rx.recv_timeout(Duration::from_millis(100)).is_ok() {}
                     
                     // Determine what to run based on what changed
                     let target_filter = determine_test_target(&changed_files);
                     
                     if let Some(specific_target) = target_filter {
                         println!("🎯 Running tests for: {}", specific_target);
-                        if let Err(e) = run_specific_test(path, &specific_target).await {
+                        if let Err(e) = run_specific_test(path, &specific_target, select).await {
                             eprintln!("Error running tests: {}", e);
                         }
                     } else {
                         // If we can't determine a specific target (e.g., Cargo.toml changed),
                         // run all tests
                         println!("🔄 Running all tests...");
-                        if let Err(e) = run_once(path, filter).await {
+                        if let Err(e) = run_once(path, filter, None, select, false).await {
                             eprintln!("Error running tests: {}", e);
                         }
                     }
@@ -177,7 +369,7 @@ fn determine_test_target(changed_files: &[&std::path::PathBuf]) -> Option<String
         // If it's a test/example file, extract the test name
         if let Some(file_name) = file_path.file_name().and_then(|n| n.to_str()) {
             // Check if it's in examples/ directory
-            if file_path.to_str().map_or(false, |p| p.contains("examples/")) {
+            if file_path.to_str().is_some_and(|p| p.contains("examples/")) {
                 // Extract the example name (without .rs extension)
                 if let Some(test_name) = file_name.strip_suffix(".rs") {
                     return Some(test_name.to_string());
@@ -185,7 +377,7 @@ fn determine_test_target(changed_files: &[&std::path::PathBuf]) -> Option<String
             }
             
             // Check if it's a test file in tests/ directory
-            if file_path.to_str().map_or(false, |p| p.contains("tests/")) {
+            if file_path.to_str().is_some_and(|p| p.contains("tests/")) {
                 if let Some(test_name) = file_name.strip_suffix(".rs") {
                     return Some(test_name.to_string());
                 }
@@ -204,19 +396,20 @@ fn determine_test_target(changed_files: &[&std::path::PathBuf]) -> Option<String
 }
 
 /// Run a specific test by name
-async fn run_specific_test(_path: &PathBuf, test_name: &str) -> anyhow::Result<()> {
+async fn run_specific_test(_path: &PathBuf, test_name: &str, select: &SelectArgs) -> anyhow::Result<()> {
     let mut cmd = Command::new("cargo");
     cmd.arg("test");
     
     // Target the specific example or test
-    cmd.args(&["--example", test_name]);
+    cmd.args(["--example", test_name]);
     
     // Enable the persistence feature
-    cmd.args(&["--features", "evalcraft-core/persistence"]);
+    cmd.args(["--features", "evalcraft-core/persistence"]);
     
     // Inject the DB path
-    let db_path = std::env::current_dir()?.join("eval_history.db");
+    let db_path = std::env::current_dir()?.join(DB_PATH);
     cmd.env("EVALCRAFT_DB_PATH", db_path);
+    select.apply_env(&mut cmd);
     
     let status = cmd.status()?;
 
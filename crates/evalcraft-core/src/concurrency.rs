@@ -0,0 +1,191 @@
+//! Adaptive (AIMD) concurrency: ramp up in-flight cases while the task is
+//! healthy, halve them when errors or rising latency suggest throttling.
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::types::{CaseResult, ConcurrencySample};
+
+/// Smoothing for the case latency moving average.
+const LATENCY_ALPHA: f64 = 0.2;
+/// Completions before the latency baseline is trusted.
+const LATENCY_WARMUP: usize = 5;
+
+/// Settings for `EvalBuilder::adaptive_concurrency`.
+///
+/// The limit starts at the builder's `concurrency` (clamped to `min..=max`), grows
+/// by one for every `limit` healthy completions, and is multiplied by `backoff`
+/// when a case fails, is retried or times out, or when the average latency rises
+/// above `latency_factor` times its best value so far.
+#[derive(Debug, Clone)]
+pub struct AdaptiveConcurrency {
+    min: usize,
+    max: usize,
+    backoff: f64,
+    latency_factor: f64,
+}
+
+impl AdaptiveConcurrency {
+    pub fn new(min: usize, max: usize) -> Self {
+        let min = min.max(1);
+        Self {
+            min,
+            max: max.max(min),
+            backoff: 0.5,
+            latency_factor: 2.0,
+        }
+    }
+
+    /// Multiplicative decrease applied on throttling, in (0, 1). Defaults to 0.5.
+    pub fn backoff(mut self, factor: f64) -> Self {
+        self.backoff = factor.clamp(0.05, 0.95);
+        self
+    }
+
+    /// How much slower than the best observed average latency counts as throttling.
+    /// Defaults to 2.0.
+    pub fn latency_factor(mut self, factor: f64) -> Self {
+        self.latency_factor = factor.max(1.0);
+        self
+    }
+}
+
+/// The in-flight limit for one run, fixed or adaptive.
+pub(crate) struct ConcurrencyLimit {
+    limit: f64,
+    adaptive: Option<AdaptiveConcurrency>,
+    started: Instant,
+    latency_avg: Option<f64>,
+    latency_best: Option<f64>,
+    completions: usize,
+    since_decrease: usize,
+    log: Vec<ConcurrencySample>,
+}
+
+impl ConcurrencyLimit {
+    pub(crate) fn new(initial: usize, adaptive: Option<AdaptiveConcurrency>) -> Self {
+        let initial = match &adaptive {
+            Some(a) => initial.clamp(a.min, a.max),
+            None => initial.max(1),
+        };
+        let mut limit = Self {
+            limit: initial as f64,
+            adaptive,
+            started: Instant::now(),
+            latency_avg: None,
+            latency_best: None,
+            completions: 0,
+            since_decrease: 0,
+            log: Vec::new(),
+        };
+        if limit.adaptive.is_some() {
+            limit.record();
+        }
+        limit
+    }
+
+    pub(crate) fn current(&self) -> usize {
+        self.limit as usize
+    }
+
+    /// Feed a finished case back into the controller.
+    pub(crate) fn on_complete(&mut self, cr: &CaseResult, latency: Duration) {
+        let Some(config) = &self.adaptive else {
+            return;
+        };
+
+        let ms = latency.as_secs_f64() * 1000.0;
+        let avg = self.latency_avg.map_or(ms, |avg| avg + LATENCY_ALPHA * (ms - avg));
+        self.latency_avg = Some(avg);
+        self.completions += 1;
+        if self.completions >= LATENCY_WARMUP {
+            self.latency_best = Some(self.latency_best.map_or(avg, |best| best.min(avg)));
+        }
+
+        let slow = self.latency_best.is_some_and(|best| avg > best * config.latency_factor);
+        let throttled = cr.error.is_some() || cr.attempts > 1 || slow;
+        let before = self.current();
+        self.since_decrease += 1;
+
+        if throttled {
+            // Failures from cases that were already in flight belong to the same
+            // congestion event, so back off at most once per window of `limit` cases.
+            if self.since_decrease >= before {
+                self.limit = (self.limit * config.backoff).max(config.min as f64);
+                self.since_decrease = 0;
+            }
+        } else {
+            self.limit = (self.limit + 1.0 / self.limit).min(config.max as f64);
+        }
+
+        if self.current() != before {
+            self.record();
+        }
+    }
+
+    pub(crate) fn take_log(&mut self) -> Vec<ConcurrencySample> {
+        std::mem::take(&mut self.log)
+    }
+
+    fn record(&mut self) {
+        self.log.push(ConcurrencySample {
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            limit: self.current(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TestCase;
+    use serde_json::json;
+
+    fn case(error: bool) -> CaseResult {
+        CaseResult {
+            index: 0,
+            case: TestCase::new(json!(null), json!(null)),
+            output: json!(null),
+            error: error.then(|| "503".to_string()),
+            error_kind: None,
+            attempts: 1,
+            attempt_errors: Vec::new(),
+            scores: Vec::new(),
+            aggregate: None,
+            traces: Vec::new(),
+            trials: Vec::new(),
+            trial_stats: None,
+        }
+    }
+
+    #[test]
+    fn test_aimd() {
+        let mut limit = ConcurrencyLimit::new(2, Some(AdaptiveConcurrency::new(1, 8)));
+        let fast = Duration::from_millis(10);
+
+        for _ in 0..20 {
+            limit.on_complete(&case(false), fast);
+        }
+        let grown = limit.current();
+        assert!(grown > 2 && grown <= 8);
+
+        limit.on_complete(&case(true), fast);
+        assert_eq!(limit.current(), grown / 2);
+        // A second failure in the same window doesn't halve again.
+        limit.on_complete(&case(true), fast);
+        assert_eq!(limit.current(), grown / 2);
+
+        let log = limit.take_log();
+        assert_eq!(log.first().map(|s| s.limit), Some(2));
+        assert_eq!(log.last().map(|s| s.limit), Some(grown / 2));
+    }
+
+    #[test]
+    fn test_fixed_limit_never_changes() {
+        let mut limit = ConcurrencyLimit::new(4, None);
+        limit.on_complete(&case(true), Duration::from_secs(5));
+        assert_eq!(limit.current(), 4);
+        assert!(limit.take_log().is_empty());
+    }
+}
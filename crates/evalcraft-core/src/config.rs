@@ -1,20 +1,141 @@
+use anyhow::{anyhow, bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::concurrency::AdaptiveConcurrency;
+use crate::datasource::{self, DataFormat, DataSource, FieldMapping};
+use crate::datasource_ext::DataSourceExt;
+use crate::policy::{PassPolicy, ScorerOptions};
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use crate::scorer::Scorer;
+use crate::scorers::{
+    contains::ContainsScorer,
+    exact::ExactMatchScorer,
+    json::JsonScorer,
+    levenshtein::LevenshteinScorer,
+    regex::RegexScorer,
+    sql::{SqlDialect, SqlScorer},
+};
+use crate::task::Task;
+use crate::tasks::{
+    cached::{CacheMode, CachedTask},
+    chat::ChatCompletionTask,
+    command::CommandTask,
+    http::HttpTask,
+};
+use crate::template;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvalConfig {
+    /// Name used when persisting results (defaults to the config file stem).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     pub task: TaskConfig,
     pub data: DataConfig,
     #[serde(default)]
-    pub scorers: Vec<ScorerConfig>,
+    pub scorers: Vec<ScorerEntry>,
+    /// How the scores decide whether a case passed (default: all required scores pass).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pass_policy: Option<PassPolicyConfig>,
     #[serde(default = "default_concurrency")]
     pub concurrency: usize,
+    /// Per-case task timeout in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_timeout: Option<f64>,
+    /// Per-scorer timeout in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scorer_timeout: Option<f64>,
+    /// How many of a case's scorers run at once (all by default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scorer_concurrency: Option<usize>,
+    /// Deadline for the whole run in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_deadline: Option<f64>,
+    /// Retry transient task failures (HTTP 429/5xx, timeouts).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    /// Provider limits for this eval's task calls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Adapt the number of in-flight cases, starting from `concurrency`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptive_concurrency: Option<AdaptiveConcurrencyConfig>,
+    /// Run every case this many times and report pass@k / flakiness.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trials: Option<usize>,
+    /// Stop starting new cases after this many failures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_fast: Option<usize>,
 }
 
 fn default_concurrency() -> usize {
     8
 }
 
+/// Convert a config value in seconds, rejecting negative, NaN and out-of-range values.
+pub(crate) fn seconds(field: &str, secs: f64) -> Result<Duration> {
+    Duration::try_from_secs_f64(secs)
+        .map_err(|_| anyhow!("Invalid {}: {} (expected a non-negative number of seconds)", field, secs))
+}
+
+impl EvalConfig {
+    /// Load a config from a YAML (or JSON) file. Relative `data.path` and
+    /// `json_schema` paths are resolved against the file's directory.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        let mut config: EvalConfig = serde_yaml::from_str(&content)
+            .with_context(|| format!("Invalid eval config {:?}", path))?;
+        if config.name.is_none() {
+            config.name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(|s| s.to_string());
+        }
+        if let Some(dir) = path.parent() {
+            config.data.path = dir.join(&config.data.path);
+            for entry in &mut config.scorers {
+                if let ScorerConfig::JsonSchema { path } = &mut entry.scorer {
+                    *path = dir.join(&*path);
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Build the task, wrapped in a `CachedTask` keyed by the task config when
+    /// `EVALCRAFT_CACHE` is `record` or `replay`.
+    pub fn build_task(&self) -> Result<Arc<dyn Task>> {
+        let mode = CacheMode::from_env()?;
+        if mode == CacheMode::Off {
+            return self.task.build();
+        }
+        let identity = self.task.cache_identity()?;
+        let task = if mode == CacheMode::Replay {
+            // Replay never calls the task, so don't require its env vars or binaries.
+            self.task.build().unwrap_or_else(|_| Arc::new(ReplayOnly))
+        } else {
+            self.task.build()?
+        };
+        Ok(Arc::new(CachedTask::new(task, identity)?.mode(mode)))
+    }
+}
+
+/// Placeholder for a task that couldn't be built in replay mode.
+struct ReplayOnly;
+
+#[async_trait::async_trait]
+impl Task for ReplayOnly {
+    async fn run(&self, _input: &serde_json::Value) -> Result<serde_json::Value> {
+        Err(anyhow!("Task is not available in replay mode"))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "snake_case")]
@@ -23,6 +144,53 @@ pub enum TaskConfig {
         url: String,
         #[serde(default = "default_http_method")]
         method: String,
+        /// Request body template, e.g. `{"messages": [{"role": "user", "content": "{{input.question}}"}]}`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body: Option<serde_json::Value>,
+        /// Extra headers; `${ENV}` references are expanded when the task is built.
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+        /// Query parameters; values may use `${ENV}` and `{{input...}}` placeholders.
+        #[serde(default)]
+        query: BTreeMap<String, String>,
+        /// JSONPath-style selector for the answer, e.g. `$.choices[0].message.content`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output: Option<String>,
+    },
+    /// Run a program per case, sending the input as JSON on stdin and reading the output from stdout.
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: BTreeMap<String, String>,
+        /// Per-case timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<f64>,
+        /// Keep one process alive and exchange one JSON line per case.
+        #[serde(default)]
+        persistent: bool,
+    },
+    /// Call an OpenAI-compatible chat-completions endpoint and record traces automatically.
+    #[serde(rename = "openai_chat")]
+    OpenAiChat {
+        /// API root, e.g. `http://localhost:8080` or `https://api.openai.com/v1`.
+        base_url: String,
+        model: String,
+        /// API key; `${ENV}` references are expanded when the task is built.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        system_prompt: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        temperature: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_tokens: Option<u32>,
+        /// Shorthand for `response_format: {type: json_object}`.
+        #[serde(default)]
+        json_mode: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        response_format: Option<serde_json::Value>,
     },
 }
 
@@ -30,9 +198,122 @@ fn default_http_method() -> String {
     "POST".to_string()
 }
 
+impl TaskConfig {
+    /// What `CachedTask` keys cassettes by. Maps are `BTreeMap`s so this is the
+    /// same in every process.
+    fn cache_identity(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn build(&self) -> Result<Arc<dyn Task>> {
+        match self {
+            TaskConfig::Http {
+                url,
+                method,
+                body,
+                headers,
+                query,
+                output,
+            } => {
+                let mut task = HttpTask::with_method(template::interpolate_env(url)?, method)?;
+                if let Some(body) = body {
+                    task = task.body(body.clone());
+                }
+                for (name, value) in headers {
+                    task = task.header(name.clone(), template::interpolate_env(value)?);
+                }
+                for (name, value) in query {
+                    task = task.query(name.clone(), template::interpolate_env(value)?);
+                }
+                if let Some(selector) = output {
+                    task = task.output(selector.clone());
+                }
+                Ok(Arc::new(task))
+            }
+            TaskConfig::Command {
+                program,
+                args,
+                env,
+                timeout,
+                persistent,
+            } => {
+                let mut task = CommandTask::new(program.clone())
+                    .args(args.clone())
+                    .persistent(*persistent);
+                for (key, value) in env {
+                    task = task.env(key.clone(), value.clone());
+                }
+                if let Some(secs) = timeout {
+                    task = task.timeout(seconds("task.timeout", *secs)?);
+                }
+                Ok(Arc::new(task))
+            }
+            TaskConfig::OpenAiChat {
+                base_url,
+                model,
+                api_key,
+                system_prompt,
+                temperature,
+                max_tokens,
+                json_mode,
+                response_format,
+            } => {
+                let mut task = ChatCompletionTask::new(template::interpolate_env(base_url)?, model.clone());
+                if let Some(key) = api_key {
+                    task = task.api_key(template::interpolate_env(key)?);
+                }
+                if let Some(prompt) = system_prompt {
+                    task = task.system_prompt(prompt.clone());
+                }
+                if let Some(t) = temperature {
+                    task = task.temperature(*t);
+                }
+                if let Some(n) = max_tokens {
+                    task = task.max_tokens(*n);
+                }
+                if let Some(format) = response_format {
+                    task = task.response_format(format.clone());
+                } else if *json_mode {
+                    task = task.json_mode();
+                }
+                Ok(Arc::new(task))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataConfig {
+    /// A file, a directory, or a glob pattern such as `data/*.csv`.
     pub path: PathBuf,
+    /// Dataset format; detected from the file extension when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<DataFormat>,
+    /// `id_field` / `input_field` / `expected_field` / `input_fields` overrides.
+    #[serde(flatten)]
+    pub fields: FieldMapping,
+    /// Shuffle all cases using `seed`.
+    #[serde(default)]
+    pub shuffle: bool,
+    /// Run a random subset of this many cases (reproducible for a given `seed`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<usize>,
+    /// Seed for `shuffle` and `sample`.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl DataConfig {
+    pub fn build(&self) -> Result<Arc<dyn DataSource>> {
+        let mut source = datasource::load_path(&self.path, self.format, &self.fields)?;
+        if let Some(n) = self.sample {
+            source = Arc::new(source.sample(n, self.seed));
+        }
+        if self.shuffle {
+            source = Arc::new(source.shuffle(self.seed));
+        }
+        Ok(source)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,3 +342,497 @@ pub enum ScorerConfig {
     },
 }
 
+/// A scorer and how it counts towards the case's aggregate score, e.g.
+/// `{type: levenshtein, threshold: 0.8, weight: 2}` or `{type: json, informational: true}`.
+/// A scorer without parameters may also be given by name (`exact`).
+///
+/// `EvalConfig::scorers` used to hold `ScorerConfig`s; convert those with
+/// `ScorerConfig::into` (or `ScorerEntry::try_from("exact")`).
+#[derive(Debug, Clone, Serialize)]
+pub struct ScorerEntry {
+    #[serde(flatten)]
+    pub scorer: ScorerConfig,
+    pub weight: f64,
+    pub informational: bool,
+}
+
+#[derive(Deserialize)]
+struct ScorerEntryFields {
+    #[serde(flatten)]
+    scorer: ScorerConfig,
+    #[serde(default = "default_weight")]
+    weight: f64,
+    #[serde(default)]
+    informational: bool,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+impl<'de> Deserialize<'de> for ScorerEntry {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error;
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(name) => ScorerEntry::try_from(name).map_err(|e| D::Error::custom(format!("{:#}", e))),
+            value => {
+                let fields: ScorerEntryFields = serde_json::from_value(value).map_err(D::Error::custom)?;
+                Ok(ScorerEntry {
+                    scorer: fields.scorer,
+                    weight: fields.weight,
+                    informational: fields.informational,
+                })
+            }
+        }
+    }
+}
+
+impl From<ScorerConfig> for ScorerEntry {
+    fn from(scorer: ScorerConfig) -> Self {
+        Self {
+            scorer,
+            weight: default_weight(),
+            informational: false,
+        }
+    }
+}
+
+impl TryFrom<&str> for ScorerEntry {
+    type Error = anyhow::Error;
+
+    /// A scorer without parameters by its `type`, e.g. `exact` or `json`.
+    fn try_from(name: &str) -> Result<Self> {
+        let scorer: ScorerConfig = serde_json::from_value(serde_json::json!({ "type": name }))
+            .with_context(|| format!("Scorer '{}' can't be given by name alone", name))?;
+        Ok(scorer.into())
+    }
+}
+
+impl TryFrom<String> for ScorerEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(name: String) -> Result<Self> {
+        ScorerEntry::try_from(name.as_str())
+    }
+}
+
+impl ScorerEntry {
+    pub fn options(&self) -> ScorerOptions {
+        let options = ScorerOptions::new().weight(self.weight);
+        if self.informational {
+            options.informational()
+        } else {
+            options
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub enum PassPolicyConfig {
+    All,
+    Any,
+    WeightedThreshold { threshold: f64 },
+    AtLeast { n: usize },
+}
+
+impl PassPolicyConfig {
+    pub fn build(&self) -> PassPolicy {
+        match self {
+            PassPolicyConfig::All => PassPolicy::All,
+            PassPolicyConfig::Any => PassPolicy::Any,
+            PassPolicyConfig::WeightedThreshold { threshold } => PassPolicy::WeightedThreshold(*threshold),
+            PassPolicyConfig::AtLeast { n } => PassPolicy::AtLeast(*n),
+        }
+    }
+}
+
+impl ScorerConfig {
+    pub fn build(&self) -> Result<Arc<dyn Scorer>> {
+        let scorer: Arc<dyn Scorer> = match self {
+            ScorerConfig::Exact => Arc::new(ExactMatchScorer),
+            ScorerConfig::Levenshtein { threshold } => Arc::new(LevenshteinScorer::new(*threshold)),
+            ScorerConfig::Contains {
+                substring,
+                case_sensitive,
+            } => {
+                if *case_sensitive {
+                    Arc::new(ContainsScorer::new(substring.clone()))
+                } else {
+                    Arc::new(ContainsScorer::case_insensitive(substring.clone()))
+                }
+            }
+            ScorerConfig::Regex { pattern } => Arc::new(RegexScorer::new(pattern)?),
+            ScorerConfig::Json => Arc::new(JsonScorer::new()),
+            ScorerConfig::JsonSchema { path } => {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read {:?}", path))?;
+                let schema = serde_json::from_str(&content)
+                    .with_context(|| format!("Invalid JSON schema in {:?}", path))?;
+                Arc::new(JsonScorer::with_schema(schema)?)
+            }
+            ScorerConfig::Sql { dialect } => Arc::new(SqlScorer::new(parse_sql_dialect(dialect)?)),
+        };
+        Ok(scorer)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Total attempts per case, including the first.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// First backoff in seconds; multiplied by `multiplier` on every retry.
+    #[serde(default = "default_initial_backoff")]
+    pub initial_backoff: f64,
+    /// Backoff cap in seconds.
+    #[serde(default = "default_max_backoff")]
+    pub max_backoff: f64,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    #[serde(default = "default_jitter")]
+    pub jitter: bool,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_initial_backoff() -> f64 {
+    0.5
+}
+
+fn default_max_backoff() -> f64 {
+    30.0
+}
+
+fn default_multiplier() -> f64 {
+    2.0
+}
+
+fn default_jitter() -> bool {
+    true
+}
+
+impl RetryConfig {
+    pub fn build(&self) -> Result<RetryPolicy> {
+        Ok(RetryPolicy::new(self.max_attempts)
+            .backoff(
+                seconds("retry.initial_backoff", self.initial_backoff)?,
+                seconds("retry.max_backoff", self.max_backoff)?,
+            )
+            .multiplier(self.multiplier)
+            .context("Invalid retry.multiplier")?
+            .jitter(self.jitter))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_per_minute: Option<u64>,
+}
+
+impl RateLimitConfig {
+    pub fn build(&self) -> Result<RateLimiter> {
+        let mut limiter = RateLimiter::new();
+        if let Some(rpm) = self.requests_per_minute {
+            limiter = limiter
+                .requests_per_minute(rpm)
+                .with_context(|| format!("Invalid rate_limit.requests_per_minute: {}", rpm))?;
+        }
+        if let Some(tpm) = self.tokens_per_minute {
+            if tpm == 0 {
+                bail!("Invalid rate_limit.tokens_per_minute: 0 (expected a positive number)");
+            }
+            limiter = limiter.tokens_per_minute(tpm);
+        }
+        Ok(limiter)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveConcurrencyConfig {
+    #[serde(default = "default_min_concurrency")]
+    pub min: usize,
+    #[serde(default = "default_max_concurrency")]
+    pub max: usize,
+}
+
+fn default_min_concurrency() -> usize {
+    1
+}
+
+fn default_max_concurrency() -> usize {
+    64
+}
+
+impl AdaptiveConcurrencyConfig {
+    pub fn build(&self) -> AdaptiveConcurrency {
+        AdaptiveConcurrency::new(self.min, self.max)
+    }
+}
+
+fn parse_sql_dialect(dialect: &str) -> Result<SqlDialect> {
+    match dialect.to_lowercase().as_str() {
+        "" | "generic" => Ok(SqlDialect::Generic),
+        "postgres" | "postgresql" => Ok(SqlDialect::PostgreSQL),
+        "mysql" => Ok(SqlDialect::MySQL),
+        "sqlite" => Ok(SqlDialect::SQLite),
+        other => Err(anyhow!("Unknown SQL dialect: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_example_config() {
+        let config = EvalConfig::from_file("../../examples/eval.yaml").unwrap();
+        assert_eq!(config.name.as_deref(), Some("eval"));
+        assert_eq!(config.concurrency, 4);
+        assert_eq!(config.scorers.len(), 5);
+        assert!(config.task.build().is_ok());
+        // Relative to the config file, not the working directory.
+        assert_eq!(config.data.path, Path::new("../../examples/data.jsonl"));
+        assert!(config.data.build().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_durations_are_errors() {
+        for value in [-1.0, f64::NAN, f64::INFINITY] {
+            let mut config = EvalConfig::from_file("../../examples/eval.yaml").unwrap();
+            config.case_timeout = Some(value);
+            let err = crate::runner::EvalBuilder::from_config(&config).err().unwrap();
+            assert!(err.to_string().contains("case_timeout"), "{}", err);
+        }
+        assert_eq!(seconds("run_deadline", 1.5).unwrap(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_build_scorers() {
+        let yaml = r#"
+- type: exact
+- type: levenshtein
+  threshold: 0.8
+- type: contains
+  substring: "World"
+- type: regex
+  pattern: ".*World.*"
+- type: json
+- type: json_schema
+  path: "../../examples/json_schema.json"
+- type: sql
+  dialect: postgres
+"#;
+        let configs: Vec<ScorerConfig> = serde_yaml::from_str(yaml).unwrap();
+        let names: Vec<&str> = configs
+            .iter()
+            .map(|c| c.build().unwrap().name())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["exact_match", "levenshtein", "contains", "regex", "json", "json", "sql"]
+        );
+    }
+
+    #[test]
+    fn test_scorer_weights_and_pass_policy() {
+        let yaml = r#"
+- type: exact
+- type: levenshtein
+  threshold: 0.8
+  weight: 2
+- type: json
+  informational: true
+"#;
+        let entries: Vec<ScorerEntry> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(entries[0].options(), ScorerOptions::new());
+        assert!(matches!(entries[0].scorer, ScorerConfig::Exact));
+        assert_eq!(entries[1].options(), ScorerOptions::new().weight(2.0));
+        assert!(matches!(entries[1].scorer, ScorerConfig::Levenshtein { threshold } if threshold == 0.8));
+        assert!(entries[2].options().is_informational());
+
+        let policy: PassPolicyConfig = serde_yaml::from_str("{type: weighted_threshold, threshold: 0.7}").unwrap();
+        assert_eq!(policy.build(), PassPolicy::WeightedThreshold(0.7));
+    }
+
+    #[test]
+    fn test_scorer_entry_shorthands() {
+        let entries: Vec<ScorerEntry> = serde_yaml::from_str("[exact, {type: json, weight: 2}]").unwrap();
+        assert!(matches!(entries[0].scorer, ScorerConfig::Exact));
+        assert_eq!(entries[1].options(), ScorerOptions::new().weight(2.0));
+
+        let err = serde_yaml::from_str::<Vec<ScorerEntry>>("[levenshtein]").unwrap_err();
+        assert!(err.to_string().contains("threshold"), "{}", err);
+        let err = serde_yaml::from_str::<Vec<ScorerEntry>>("[{type: levenshtein}]").unwrap_err();
+        assert!(err.to_string().contains("threshold"), "{}", err);
+
+        let entry: ScorerEntry = ScorerConfig::Levenshtein { threshold: 0.8 }.into();
+        assert_eq!(entry.options(), ScorerOptions::new());
+        assert!(matches!(ScorerEntry::try_from("json").unwrap().scorer, ScorerConfig::Json));
+    }
+
+    #[test]
+    fn test_http_task_config() {
+        std::env::set_var("EVALCRAFT_CONFIG_TEST_KEY", "sk-test");
+        let yaml = r#"
+type: http
+url: "http://localhost:8080/chat/completions"
+body:
+  model: "Qwen3-0.5b"
+  messages:
+    - role: user
+      content: "{{input}}"
+headers:
+  Authorization: "Bearer ${EVALCRAFT_CONFIG_TEST_KEY}"
+output: "$.choices[0].message.content"
+"#;
+        let config: TaskConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.build().is_ok());
+
+        let missing = TaskConfig::Http {
+            url: "http://localhost".to_string(),
+            method: "POST".to_string(),
+            body: None,
+            headers: BTreeMap::from([(
+                "Authorization".to_string(),
+                "Bearer ${EVALCRAFT_CONFIG_TEST_UNSET}".to_string(),
+            )]),
+            query: BTreeMap::new(),
+            output: None,
+        };
+        assert!(missing.build().is_err());
+    }
+
+    #[test]
+    fn test_cache_identity_is_stable() {
+        let yaml = r#"
+type: http
+url: "http://localhost:8080/chat"
+headers:
+  Authorization: "Bearer ${EVALCRAFT_CONFIG_TEST_KEY}"
+  X-Team: evals
+  X-Model: qwen
+  Accept: application/json
+query:
+  a: "1"
+  b: "2"
+"#;
+        let parse = || serde_yaml::from_str::<TaskConfig>(yaml).unwrap().cache_identity().unwrap();
+        assert_eq!(parse(), parse());
+    }
+
+    #[test]
+    fn test_openai_chat_task_config() {
+        let yaml = r#"
+type: openai_chat
+base_url: "http://localhost:8080"
+model: "Qwen3-0.5b"
+system_prompt: "Answer in one word."
+temperature: 0.0
+max_tokens: 32
+json_mode: true
+"#;
+        let config: TaskConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(config, TaskConfig::OpenAiChat { json_mode: true, .. }));
+        assert!(config.build().is_ok());
+    }
+
+    #[test]
+    fn test_data_config_fields() {
+        let yaml = r#"
+path: "qa.csv"
+input_fields: ["question", "context"]
+expected_field: "answer"
+"#;
+        let config: DataConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.fields.id_field, "id");
+        assert_eq!(config.fields.expected_field, "answer");
+        assert_eq!(config.fields.input_fields, vec!["question", "context"]);
+        assert!(!config.shuffle);
+        assert_eq!(config.sample, None);
+    }
+
+    #[tokio::test]
+    async fn test_data_config_sample() {
+        let yaml = r#"
+path: "../../examples/llm_testing.jsonl"
+sample: 3
+seed: 42
+"#;
+        let config: DataConfig = serde_yaml::from_str(yaml).unwrap();
+        let first = config.build().unwrap().load().await.unwrap();
+        let second = config.build().unwrap().load().await.unwrap();
+        assert_eq!(first.len(), 3);
+        let ids = |cases: &[crate::types::TestCase]| cases.iter().map(|c| c.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&first), ids(&second));
+    }
+
+    #[test]
+    fn test_command_task_config() {
+        let yaml = r#"
+type: command
+program: python3
+args: ["agent.py"]
+env:
+  MODEL: small
+timeout: 2.5
+persistent: true
+"#;
+        let config: TaskConfig = serde_yaml::from_str(yaml).unwrap();
+        match &config {
+            TaskConfig::Command { args, timeout, persistent, .. } => {
+                assert_eq!(args, &vec!["agent.py".to_string()]);
+                assert_eq!(*timeout, Some(2.5));
+                assert!(*persistent);
+            }
+            other => panic!("unexpected task config: {:?}", other),
+        }
+        assert!(config.build().is_ok());
+    }
+
+    #[test]
+    fn test_command_task_invalid_timeout() {
+        let config: TaskConfig = serde_yaml::from_str("{type: command, program: cat, timeout: -1}").unwrap();
+        let err = config.build().err().unwrap();
+        assert!(err.to_string().contains("task.timeout"), "{}", err);
+    }
+
+    #[test]
+    fn test_retry_config_invalid_backoff() {
+        let config: RetryConfig = serde_yaml::from_str("{max_attempts: 3, max_backoff: -5}").unwrap();
+        let err = config.build().unwrap_err();
+        assert!(err.to_string().contains("retry.max_backoff"), "{}", err);
+        let config: RetryConfig = serde_yaml::from_str("{initial_backoff: .nan}").unwrap();
+        assert!(config.build().is_err());
+        let config: RetryConfig = serde_yaml::from_str("{multiplier: .inf}").unwrap();
+        let err = config.build().unwrap_err();
+        assert!(err.to_string().contains("retry.multiplier"), "{}", err);
+    }
+
+    #[test]
+    fn test_rate_limit_config_invalid_rates() {
+        for yaml in ["{requests_per_minute: 1e-20}", "{requests_per_minute: -5}", "{requests_per_minute: .nan}"] {
+            let config: RateLimitConfig = serde_yaml::from_str(yaml).unwrap();
+            let err = config.build().unwrap_err();
+            assert!(err.to_string().contains("rate_limit.requests_per_minute"), "{}", err);
+        }
+        let config: RateLimitConfig = serde_yaml::from_str("{tokens_per_minute: 0}").unwrap();
+        assert!(config.build().is_err());
+        let config: RateLimitConfig = serde_yaml::from_str("{requests_per_minute: 500, tokens_per_minute: 200000}").unwrap();
+        assert!(config.build().is_ok());
+    }
+
+    #[test]
+    fn test_unknown_sql_dialect() {
+        let config = ScorerConfig::Sql {
+            dialect: "oracle".to_string(),
+        };
+        assert!(config.build().is_err());
+    }
+}
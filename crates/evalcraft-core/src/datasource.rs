@@ -1,7 +1,10 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
+use futures::future;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::types::TestCase;
@@ -9,6 +12,23 @@ use crate::types::TestCase;
 #[async_trait]
 pub trait DataSource: Send + Sync {
     async fn load(&self) -> Result<Vec<TestCase>>;
+
+    /// Yield cases one at a time. The runner consumes this lazily, so sources
+    /// that can read incrementally should override it; the default loads
+    /// everything with `load` first.
+    fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+        stream::once(self.load())
+            .flat_map(|loaded| match loaded {
+                Ok(cases) => stream::iter(cases.into_iter().map(Ok)).boxed(),
+                Err(e) => stream::iter([Err(e)]).boxed(),
+            })
+            .boxed()
+    }
+
+    /// Number of cases, if known without reading them (used for progress reporting).
+    fn size_hint(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub struct VecDataSource {
@@ -26,18 +46,224 @@ impl DataSource for VecDataSource {
     async fn load(&self) -> Result<Vec<TestCase>> {
         Ok(self.cases.clone())
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.cases.len())
+    }
+}
+
+/// On-disk dataset format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataFormat {
+    /// One JSON case object per line (`.jsonl`, `.ndjson`).
+    Jsonl,
+    /// A JSON array of case objects (`.json`).
+    Json,
+    /// A YAML sequence of case mappings (`.yaml`, `.yml`).
+    Yaml,
+    /// A CSV file with a header row (`.csv`).
+    Csv,
+}
+
+impl DataFormat {
+    /// Detect the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "jsonl" | "ndjson" => Some(DataFormat::Jsonl),
+            "json" => Some(DataFormat::Json),
+            "yaml" | "yml" => Some(DataFormat::Yaml),
+            "csv" => Some(DataFormat::Csv),
+            _ => None,
+        }
+    }
+
+    fn parse(self, content: &str, fields: &FieldMapping) -> Result<Vec<TestCase>> {
+        match self {
+            DataFormat::Jsonl => parse_jsonl(content, fields),
+            DataFormat::Json => parse_json_array(content, fields),
+            DataFormat::Yaml => parse_yaml(content, fields),
+            DataFormat::Csv => parse_csv(content, fields),
+        }
+    }
+}
+
+/// Which keys of a raw record hold the case id, input and expected output.
+///
+/// Fields are dotted paths (`data.prompt`, `turns[0].text`). When `input_fields` is
+/// non-empty, `input` is built as an object from those fields, keyed by each
+/// field's last path segment; fields whose last segments clash (`a.text`,
+/// `b.text`) are rejected. A missing `expected` field yields `null`.
+///
+/// Tags may be a list of strings or a comma-separated string (handy for CSV).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldMapping {
+    #[serde(default = "default_id_field")]
+    pub id_field: String,
+    #[serde(default = "default_input_field")]
+    pub input_field: String,
+    #[serde(default = "default_expected_field")]
+    pub expected_field: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "deserialize_input_fields")]
+    pub input_fields: Vec<String>,
+    #[serde(default = "default_tags_field")]
+    pub tags_field: String,
+    #[serde(default = "default_metadata_field")]
+    pub metadata_field: String,
+}
+
+fn default_id_field() -> String {
+    "id".to_string()
+}
+
+fn default_input_field() -> String {
+    "input".to_string()
+}
+
+fn default_expected_field() -> String {
+    "expected".to_string()
+}
+
+fn default_tags_field() -> String {
+    "tags".to_string()
+}
+
+fn default_metadata_field() -> String {
+    "metadata".to_string()
+}
+
+fn deserialize_input_fields<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let fields = Vec::<String>::deserialize(deserializer)?;
+    for (i, path) in fields.iter().enumerate() {
+        if let Some(other) = fields[..i].iter().find(|other| input_key(other) == input_key(path)) {
+            return Err(serde::de::Error::custom(format!(
+                "input_fields '{}' and '{}' both map to key '{}'",
+                other,
+                path,
+                input_key(path)
+            )));
+        }
+    }
+    Ok(fields)
+}
+
+/// The key a field in `input_fields` gets in the input object.
+fn input_key(path: &str) -> &str {
+    path.rsplit('.').next().unwrap_or(path)
+}
+
+impl Default for FieldMapping {
+    fn default() -> Self {
+        Self {
+            id_field: default_id_field(),
+            input_field: default_input_field(),
+            expected_field: default_expected_field(),
+            input_fields: Vec::new(),
+            tags_field: default_tags_field(),
+            metadata_field: default_metadata_field(),
+        }
+    }
+}
+
+impl FieldMapping {
+    pub fn new(
+        id_field: impl Into<String>,
+        input_field: impl Into<String>,
+        expected_field: impl Into<String>,
+    ) -> Self {
+        Self {
+            id_field: id_field.into(),
+            input_field: input_field.into(),
+            expected_field: expected_field.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Build `input` as an object from several fields instead of a single one.
+    pub fn input_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.input_fields = fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn case_from_value(&self, value: &Value, location: &str) -> Result<TestCase> {
+        if !value.is_object() {
+            bail!("{}: expected object", location);
+        }
+        let input = if self.input_fields.is_empty() {
+            field(value, &self.input_field)
+                .cloned()
+                .ok_or_else(|| anyhow!("{}: missing '{}'", location, self.input_field))?
+        } else {
+            let mut input = serde_json::Map::new();
+            for path in &self.input_fields {
+                let v = field(value, path).ok_or_else(|| anyhow!("{}: missing '{}'", location, path))?;
+                if input.insert(input_key(path).to_string(), v.clone()).is_some() {
+                    bail!("{}: input_fields '{}' clashes with another field on key '{}'", location, path, input_key(path));
+                }
+            }
+            Value::Object(input)
+        };
+        let expected = field(value, &self.expected_field).cloned().unwrap_or(Value::Null);
+        let id = field(value, &self.id_field).and_then(|v| match v {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        });
+        let tags = match field(value, &self.tags_field) {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|t| t.as_str().map(str::to_string))
+                .collect(),
+            Some(Value::String(s)) => s
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let metadata = field(value, &self.metadata_field).cloned().unwrap_or(Value::Null);
+        Ok(TestCase {
+            id,
+            input,
+            expected,
+            tags,
+            metadata,
+        })
+    }
+}
+
+/// Look up a field by exact key first (CSV headers may contain dots), then as a path.
+fn field<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    value.get(path).or_else(|| crate::template::select(value, path))
 }
 
 /// Read JSONL where each line is either:
 /// - {"id": "...", "input": ..., "expected": ...}
 /// - {"input": ..., "expected": ...}
+///
+/// Use `fields` to read other keys, e.g. `{"question": ..., "answer": ...}`.
 pub struct JsonlDataSource {
     path: PathBuf,
+    fields: FieldMapping,
 }
 
 impl JsonlDataSource {
     pub fn new(path: impl Into<PathBuf>) -> Self {
-        Self { path: path.into() }
+        Self {
+            path: path.into(),
+            fields: FieldMapping::default(),
+        }
+    }
+
+    pub fn fields(mut self, fields: FieldMapping) -> Self {
+        self.fields = fields;
+        self
     }
 }
 
@@ -45,52 +271,302 @@ impl JsonlDataSource {
 impl DataSource for JsonlDataSource {
     async fn load(&self) -> Result<Vec<TestCase>> {
         let content = tokio_fs_read_to_string(&self.path).await?;
-        let mut cases = Vec::new();
-        for (idx, line) in content.lines().enumerate() {
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
+        parse_jsonl(&content, &self.fields)
+    }
+
+    /// Read the file line by line instead of loading it whole. The stream ends
+    /// after the first error.
+    fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+        read_lines(&self.path)
+            .enumerate()
+            .filter_map(move |(idx, line)| {
+                future::ready(match line {
+                    Ok(line) => parse_jsonl_line(&line, idx + 1, &self.fields).transpose(),
+                    Err(e) => Some(Err(e)),
+                })
+            })
+            .scan(false, |failed, item| {
+                if *failed {
+                    return future::ready(None);
+                }
+                *failed = item.is_err();
+                future::ready(Some(item))
+            })
+            .boxed()
+    }
+}
+
+/// Read a JSON file holding an array of case objects.
+pub struct JsonArrayDataSource {
+    path: PathBuf,
+    fields: FieldMapping,
+}
+
+impl JsonArrayDataSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            fields: FieldMapping::default(),
+        }
+    }
+
+    pub fn fields(mut self, fields: FieldMapping) -> Self {
+        self.fields = fields;
+        self
+    }
+}
+
+#[async_trait]
+impl DataSource for JsonArrayDataSource {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let content = tokio_fs_read_to_string(&self.path).await?;
+        parse_json_array(&content, &self.fields).with_context(|| format!("In {:?}", self.path))
+    }
+}
+
+/// Read a YAML file holding a sequence of case mappings.
+pub struct YamlDataSource {
+    path: PathBuf,
+    fields: FieldMapping,
+}
+
+impl YamlDataSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            fields: FieldMapping::default(),
+        }
+    }
+
+    pub fn fields(mut self, fields: FieldMapping) -> Self {
+        self.fields = fields;
+        self
+    }
+}
+
+#[async_trait]
+impl DataSource for YamlDataSource {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let content = tokio_fs_read_to_string(&self.path).await?;
+        parse_yaml(&content, &self.fields).with_context(|| format!("In {:?}", self.path))
+    }
+}
+
+/// Read a CSV file with a header row containing `input`, `expected` and optionally `id`.
+/// Cells are read as strings.
+pub struct CsvDataSource {
+    path: PathBuf,
+    fields: FieldMapping,
+}
+
+impl CsvDataSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            fields: FieldMapping::default(),
+        }
+    }
+
+    pub fn fields(mut self, fields: FieldMapping) -> Self {
+        self.fields = fields;
+        self
+    }
+}
+
+#[async_trait]
+impl DataSource for CsvDataSource {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let content = tokio_fs_read_to_string(&self.path).await?;
+        parse_csv(&content, &self.fields).with_context(|| format!("In {:?}", self.path))
+    }
+}
+
+/// Load every dataset file in a directory or matching a glob pattern (e.g. `data/*.csv`).
+///
+/// Files are read in sorted order and concatenated. Case ids are prefixed with the
+/// file stem (`capitals/france`) so they stay unique across files; cases without
+/// an id get a prefixed `TestCase::content_id` (`capitals/case-...`).
+pub struct GlobDataSource {
+    pattern: String,
+    format: Option<DataFormat>,
+    fields: FieldMapping,
+}
+
+impl GlobDataSource {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            format: None,
+            fields: FieldMapping::default(),
+        }
+    }
+
+    pub fn fields(mut self, fields: FieldMapping) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Force a format instead of detecting it from each file's extension.
+    pub fn format(mut self, format: DataFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    fn files(&self) -> Result<Vec<(PathBuf, DataFormat)>> {
+        let dir = Path::new(&self.pattern);
+        let mut files = Vec::new();
+        if dir.is_dir() {
+            for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read {:?}", dir))? {
+                let path = entry?.path();
+                if !path.is_file() {
+                    continue;
+                }
+                // Skip unrelated files (READMEs, schemas, ...) when scanning a directory.
+                if let Some(format) = self.format.or_else(|| DataFormat::from_path(&path)) {
+                    files.push((path, format));
+                }
             }
-            let value: Value = serde_json::from_str(line)
-                .with_context(|| format!("Invalid JSON on line {}", idx + 1))?;
-            let obj = value
-                .as_object()
-                .ok_or_else(|| anyhow!("Line {}: expected object", idx + 1))?;
-            let input = obj
-                .get("input")
-                .cloned()
-                .ok_or_else(|| anyhow!("Line {}: missing 'input'", idx + 1))?;
-            let expected = obj
-                .get("expected")
-                .cloned()
-                .ok_or_else(|| anyhow!("Line {}: missing 'expected'", idx + 1))?;
-            let id = obj
-                .get("id")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
-            cases.push(TestCase {
-                id,
-                input,
-                expected,
-            });
+        } else {
+            for path in glob::glob(&self.pattern).with_context(|| format!("Invalid glob {:?}", self.pattern))? {
+                let path = path?;
+                let format = self
+                    .format
+                    .or_else(|| DataFormat::from_path(&path))
+                    .ok_or_else(|| anyhow!("Cannot detect data format of {:?}", path))?;
+                files.push((path, format));
+            }
+        }
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(files)
+    }
+}
+
+#[async_trait]
+impl DataSource for GlobDataSource {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let files = self.files()?;
+        if files.is_empty() {
+            bail!("No data files found for {:?}", self.pattern);
+        }
+
+        let mut cases = Vec::new();
+        for (path, format) in files {
+            let content = tokio_fs_read_to_string(&path).await?;
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+            let loaded = format.parse(&content, &self.fields).with_context(|| format!("In {:?}", path))?;
+            cases.extend(loaded.into_iter().map(|mut case| {
+                let id = case.id.take().unwrap_or_else(|| case.content_id());
+                case.id = Some(format!("{}/{}", stem, id));
+                case
+            }));
         }
         Ok(cases)
     }
 }
 
+/// Build a data source for a file, directory or glob pattern.
+///
+/// When `format` is `None` it is detected from the file extension.
+pub fn load_path(
+    path: &Path,
+    format: Option<DataFormat>,
+    fields: &FieldMapping,
+) -> Result<std::sync::Arc<dyn DataSource>> {
+    use std::sync::Arc;
+
+    let pattern = path.to_string_lossy();
+    if path.is_dir() || pattern.contains(['*', '?', '[']) {
+        let mut source = GlobDataSource::new(pattern.into_owned()).fields(fields.clone());
+        if let Some(format) = format {
+            source = source.format(format);
+        }
+        return Ok(Arc::new(source));
+    }
+
+    let format = format
+        .or_else(|| DataFormat::from_path(path))
+        .ok_or_else(|| anyhow!("Cannot detect data format of {:?}; set `format`", path))?;
+    let path = path.to_path_buf();
+    let fields = fields.clone();
+    Ok(match format {
+        DataFormat::Jsonl => Arc::new(JsonlDataSource::new(path).fields(fields)),
+        DataFormat::Json => Arc::new(JsonArrayDataSource::new(path).fields(fields)),
+        DataFormat::Yaml => Arc::new(YamlDataSource::new(path).fields(fields)),
+        DataFormat::Csv => Arc::new(CsvDataSource::new(path).fields(fields)),
+    })
+}
+
+fn parse_jsonl(content: &str, fields: &FieldMapping) -> Result<Vec<TestCase>> {
+    let mut cases = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        if let Some(case) = parse_jsonl_line(line, idx + 1, fields)? {
+            cases.push(case);
+        }
+    }
+    Ok(cases)
+}
+
+/// Parse one JSONL line (1-based `line_no`); blank lines yield `None`.
+fn parse_jsonl_line(line: &str, line_no: usize, fields: &FieldMapping) -> Result<Option<TestCase>> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let value: Value = serde_json::from_str(line)
+        .with_context(|| format!("Invalid JSON on line {}", line_no))?;
+    fields.case_from_value(&value, &format!("Line {}", line_no)).map(Some)
+}
+
+fn parse_json_array(content: &str, fields: &FieldMapping) -> Result<Vec<TestCase>> {
+    let value: Value = serde_json::from_str(content).context("Invalid JSON")?;
+    cases_from_array(&value, fields)
+}
+
+fn parse_yaml(content: &str, fields: &FieldMapping) -> Result<Vec<TestCase>> {
+    let value: Value = serde_yaml::from_str(content).context("Invalid YAML")?;
+    cases_from_array(&value, fields)
+}
+
+fn cases_from_array(value: &Value, fields: &FieldMapping) -> Result<Vec<TestCase>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow!("Expected a list of cases"))?
+        .iter()
+        .enumerate()
+        .map(|(idx, v)| fields.case_from_value(v, &format!("Case {}", idx + 1)))
+        .collect()
+}
+
+fn parse_csv(content: &str, fields: &FieldMapping) -> Result<Vec<TestCase>> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers().context("Invalid CSV header")?.clone();
+    let mut cases = Vec::new();
+    for (idx, record) in reader.records().enumerate() {
+        // Row numbers count the header as row 1.
+        let record = record.with_context(|| format!("Invalid CSV on row {}", idx + 2))?;
+        let row: serde_json::Map<String, Value> = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(h, v)| (h.to_string(), Value::String(v.to_string())))
+            .collect();
+        cases.push(fields.case_from_value(&Value::Object(row), &format!("Row {}", idx + 2))?);
+    }
+    Ok(cases)
+}
+
 #[cfg(not(feature = "sync-fs"))]
-async fn tokio_fs_read_to_string(path: &PathBuf) -> Result<String> {
+async fn tokio_fs_read_to_string(path: &Path) -> Result<String> {
     use tokio::fs;
-    Ok(fs::read_to_string(path)
+    fs::read_to_string(path)
         .await
-        .with_context(|| format!("Failed to read {:?}", path))?)
+        .with_context(|| format!("Failed to read {:?}", path))
 }
 
 #[cfg(feature = "sync-fs")]
-async fn tokio_fs_read_to_string(path: &PathBuf) -> Result<String> {
+async fn tokio_fs_read_to_string(path: &Path) -> Result<String> {
     use std::fs;
     use tokio::task;
-    let path_clone = path.clone();
+    let path_clone = path.to_path_buf();
     let content = task::spawn_blocking(move || {
         fs::read_to_string(&path_clone).with_context(|| format!("Failed to read {:?}", path_clone))
     })
@@ -98,3 +574,184 @@ async fn tokio_fs_read_to_string(path: &PathBuf) -> Result<String> {
     .map_err(|e| anyhow!(e))??;
     Ok(content)
 }
+
+/// Read a file's lines lazily; a read error is yielded as the last item.
+#[cfg(not(feature = "sync-fs"))]
+fn read_lines(path: &Path) -> BoxStream<'static, Result<String>> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let path = path.to_path_buf();
+    let open = async move {
+        let file = tokio::fs::File::open(&path)
+            .await
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        Ok((BufReader::new(file).lines(), path))
+    };
+
+    stream::once(open)
+        .flat_map(|opened: Result<_>| match opened {
+            Ok(state) => stream::unfold(Some(state), |state| async move {
+                let (mut lines, path) = state?;
+                match lines.next_line().await {
+                    Ok(Some(line)) => Some((Ok(line), Some((lines, path)))),
+                    Ok(None) => None,
+                    Err(e) => Some((Err(anyhow!(e).context(format!("Failed to read {:?}", path))), None)),
+                }
+            })
+            .boxed(),
+            Err(e) => stream::iter([Err(e)]).boxed(),
+        })
+        .boxed()
+}
+
+/// Read a file's lines lazily on a blocking thread; a read error is yielded as
+/// the last item.
+#[cfg(feature = "sync-fs")]
+fn read_lines(path: &Path) -> BoxStream<'static, Result<String>> {
+    use std::io::{BufRead, BufReader};
+
+    let path = path.to_path_buf();
+    let spawn = async move {
+        // Bounded, so the reader stays at most a few lines ahead of the consumer.
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::task::spawn_blocking(move || {
+            let read = |e: std::io::Error| anyhow!(e).context(format!("Failed to read {:?}", path));
+            let file = match std::fs::File::open(&path) {
+                Ok(file) => file,
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(read(e)));
+                    return;
+                }
+            };
+            for line in BufReader::new(file).lines() {
+                let failed = line.is_err();
+                if tx.blocking_send(line.map_err(read)).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|line| (line, rx)) })
+    };
+    stream::once(spawn).flatten().boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Create a fresh scratch directory under the system temp dir.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("evalcraft-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(DataFormat::from_path(Path::new("a.jsonl")), Some(DataFormat::Jsonl));
+        assert_eq!(DataFormat::from_path(Path::new("a.JSON")), Some(DataFormat::Json));
+        assert_eq!(DataFormat::from_path(Path::new("a.yml")), Some(DataFormat::Yaml));
+        assert_eq!(DataFormat::from_path(Path::new("a.csv")), Some(DataFormat::Csv));
+        assert_eq!(DataFormat::from_path(Path::new("a.txt")), None);
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let csv = "id,input,expected\ncapital,\"What is the capital of France, again?\",Paris\n";
+        let cases = parse_csv(csv, &FieldMapping::default()).unwrap();
+        assert_eq!(cases[0].id.as_deref(), Some("capital"));
+        assert_eq!(cases[0].input, json!("What is the capital of France, again?"));
+
+        let yaml = "- id: a\n  input: {question: hi}\n  expected: hello\n";
+        let cases = parse_yaml(yaml, &FieldMapping::default()).unwrap();
+        assert_eq!(cases[0].input, json!({"question": "hi"}));
+
+        let array = r#"[{"id": 1, "input": "x", "expected": "y"}]"#;
+        let cases = parse_json_array(array, &FieldMapping::default()).unwrap();
+        assert_eq!(cases[0].id.as_deref(), Some("1"));
+
+        let err = parse_csv("id,expected\n1,x\n", &FieldMapping::default()).unwrap_err();
+        assert!(err.to_string().contains("Row 2: missing 'input'"));
+    }
+
+    #[test]
+    fn test_field_mapping() {
+        let fields = FieldMapping::new("uid", "data.prompt", "answer");
+        let jsonl = r#"{"uid": 7, "data": {"prompt": "2+2"}, "answer": "4"}
+{"uid": 8, "data": {"prompt": "write a poem"}}"#;
+        let cases = parse_jsonl(jsonl, &fields).unwrap();
+        assert_eq!(cases[0].id.as_deref(), Some("7"));
+        assert_eq!(cases[0].input, json!("2+2"));
+        assert_eq!(cases[0].expected, json!("4"));
+        assert_eq!(cases[1].expected, Value::Null);
+
+        let jsonl = r#"{"input": "2+2", "expected": "4", "tags": ["math", "easy"], "metadata": {"source": "gsm"}}"#;
+        let cases = parse_jsonl(jsonl, &FieldMapping::default()).unwrap();
+        assert_eq!(cases[0].tags, vec!["math", "easy"]);
+        assert_eq!(cases[0].metadata, json!({"source": "gsm"}));
+
+        let cases = parse_csv("input,tags\nx,\"math, slow\"\n", &FieldMapping::default()).unwrap();
+        assert_eq!(cases[0].tags, vec!["math", "slow"]);
+
+        let fields = FieldMapping::default().input_fields(["question", "context"]);
+        let csv = "question,context,expected\nWho?,Alice wrote it,Alice\n";
+        let cases = parse_csv(csv, &fields).unwrap();
+        assert_eq!(cases[0].input, json!({"question": "Who?", "context": "Alice wrote it"}));
+
+        let clash = FieldMapping::default().input_fields(["a.text", "b.text"]);
+        let err = parse_jsonl(r#"{"a": {"text": 1}, "b": {"text": 2}}"#, &clash).unwrap_err();
+        assert!(err.to_string().contains("'b.text'"), "{}", err);
+        let err = serde_yaml::from_str::<FieldMapping>("input_fields: [a.text, b.text]").unwrap_err();
+        assert!(err.to_string().contains("both map to key 'text'"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_stream() {
+        let dir = scratch_dir("stream");
+        let path = dir.join("cases.jsonl");
+        std::fs::write(&path, "{\"id\":\"a\",\"input\":1}\n\n{\"id\":\"b\",\"input\":2}\nnot json\n").unwrap();
+
+        let source = JsonlDataSource::new(&path);
+        let items: Vec<Result<TestCase>> = source.stream().collect().await;
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1].as_ref().unwrap().id.as_deref(), Some("b"));
+        assert!(items[2].as_ref().unwrap_err().to_string().contains("line 4"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_glob_prefixes_ids() {
+        let dir = scratch_dir("glob");
+        std::fs::write(dir.join("math.jsonl"), "{\"id\":\"1\",\"input\":\"2+2\",\"expected\":\"4\"}\n").unwrap();
+        std::fs::write(dir.join("geo.csv"), "id,input,expected\n1,France,Paris\n").unwrap();
+        std::fs::write(dir.join("README.md"), "not data").unwrap();
+
+        let cases = load_path(&dir, None, &FieldMapping::default()).unwrap().load().await.unwrap();
+        let ids: Vec<_> = cases.iter().map(|c| c.id.clone().unwrap()).collect();
+        assert_eq!(ids, vec!["geo/1", "math/1"]);
+
+        let pattern = dir.join("*.jsonl");
+        let cases = load_path(&pattern, None, &FieldMapping::default()).unwrap().load().await.unwrap();
+        assert_eq!(cases.len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_glob_prefixes_content_ids() {
+        let dir = scratch_dir("glob-content");
+        let line = "{\"input\":\"2+2\",\"expected\":\"4\"}\n";
+        std::fs::write(dir.join("a.jsonl"), line).unwrap();
+        std::fs::write(dir.join("b.jsonl"), line).unwrap();
+
+        let cases = load_path(&dir, None, &FieldMapping::default()).unwrap().load().await.unwrap();
+        let content_id = cases[0].content_id();
+        let ids: Vec<_> = cases.iter().map(|c| c.id.clone().unwrap()).collect();
+        assert_eq!(ids, vec![format!("a/{}", content_id), format!("b/{}", content_id)]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,365 @@
+//! Composable adapters over `DataSource`s (filter, map, take, shuffle, ...).
+//!
+//! ```ignore
+//! let smoke = JsonlDataSource::new("examples/llm_testing.jsonl")
+//!     .filter(|c| c.id.as_deref().is_some_and(|id| id.starts_with("capital_")))
+//!     .sample(50, 42);
+//! let eval = Eval::builder().data_source(Arc::new(smoke)) /* ... */;
+//! ```
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future;
+use futures::stream::{BoxStream, StreamExt, TryStreamExt};
+
+use crate::datasource::DataSource;
+use crate::types::TestCase;
+
+#[async_trait]
+impl<T: DataSource + ?Sized> DataSource for Arc<T> {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        (**self).load().await
+    }
+
+    fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+        (**self).stream()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        (**self).size_hint()
+    }
+}
+
+/// Adapters available on every `DataSource`. Each returns a new `DataSource`.
+pub trait DataSourceExt: DataSource + Sized {
+    /// Keep only cases matching `pred`.
+    fn filter<F>(self, pred: F) -> Filter<Self, F>
+    where
+        F: Fn(&TestCase) -> bool + Send + Sync,
+    {
+        Filter { inner: self, pred }
+    }
+
+    /// Transform every case (e.g. rewrite inputs).
+    fn map<F>(self, f: F) -> Map<Self, F>
+    where
+        F: Fn(TestCase) -> TestCase + Send + Sync,
+    {
+        Map { inner: self, f }
+    }
+
+    /// Keep the first `n` cases.
+    fn take(self, n: usize) -> Take<Self> {
+        Take { inner: self, n }
+    }
+
+    /// Drop the first `n` cases.
+    fn skip(self, n: usize) -> Skip<Self> {
+        Skip { inner: self, n }
+    }
+
+    /// Shuffle all cases; the same seed always gives the same order.
+    fn shuffle(self, seed: u64) -> Shuffle<Self> {
+        Shuffle { inner: self, seed }
+    }
+
+    /// Pick `n` cases at random (reproducible for a given seed), keeping dataset order.
+    fn sample(self, n: usize, seed: u64) -> Sample<Self> {
+        Sample { inner: self, n, seed }
+    }
+
+    /// Append the cases of `other` after this source's cases.
+    fn concat<B: DataSource>(self, other: B) -> Concat<Self, B> {
+        Concat { first: self, second: other }
+    }
+
+    /// Drop cases whose key was already seen, keeping the first occurrence.
+    fn dedupe_by<F, K>(self, key: F) -> DedupeBy<Self, F>
+    where
+        F: Fn(&TestCase) -> K + Send + Sync,
+        K: Eq + Hash + Send + 'static,
+    {
+        DedupeBy { inner: self, key }
+    }
+}
+
+impl<T: DataSource + Sized> DataSourceExt for T {}
+
+pub struct Filter<S, F> {
+    inner: S,
+    pred: F,
+}
+
+#[async_trait]
+impl<S, F> DataSource for Filter<S, F>
+where
+    S: DataSource,
+    F: Fn(&TestCase) -> bool + Send + Sync,
+{
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let mut cases = self.inner.load().await?;
+        cases.retain(|c| (self.pred)(c));
+        Ok(cases)
+    }
+
+    fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+        self.inner
+            .stream()
+            .try_filter(move |c| future::ready((self.pred)(c)))
+            .boxed()
+    }
+}
+
+pub struct Map<S, F> {
+    inner: S,
+    f: F,
+}
+
+#[async_trait]
+impl<S, F> DataSource for Map<S, F>
+where
+    S: DataSource,
+    F: Fn(TestCase) -> TestCase + Send + Sync,
+{
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        Ok(self.inner.load().await?.into_iter().map(&self.f).collect())
+    }
+
+    fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+        self.inner.stream().map_ok(&self.f).boxed()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+pub struct Take<S> {
+    inner: S,
+    n: usize,
+}
+
+#[async_trait]
+impl<S: DataSource> DataSource for Take<S> {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let mut cases = self.inner.load().await?;
+        cases.truncate(self.n);
+        Ok(cases)
+    }
+
+    fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+        self.inner.stream().take(self.n).boxed()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint().map(|len| len.min(self.n))
+    }
+}
+
+pub struct Skip<S> {
+    inner: S,
+    n: usize,
+}
+
+#[async_trait]
+impl<S: DataSource> DataSource for Skip<S> {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        Ok(self.inner.load().await?.into_iter().skip(self.n).collect())
+    }
+
+    fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+        self.inner.stream().skip(self.n).boxed()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint().map(|len| len.saturating_sub(self.n))
+    }
+}
+
+pub struct Shuffle<S> {
+    inner: S,
+    seed: u64,
+}
+
+#[async_trait]
+impl<S: DataSource> DataSource for Shuffle<S> {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let mut cases = self.inner.load().await?;
+        shuffle(&mut cases, self.seed);
+        Ok(cases)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+pub struct Sample<S> {
+    inner: S,
+    n: usize,
+    seed: u64,
+}
+
+#[async_trait]
+impl<S: DataSource> DataSource for Sample<S> {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let cases = self.inner.load().await?;
+        let mut picked: Vec<usize> = (0..cases.len()).collect();
+        shuffle(&mut picked, self.seed);
+        picked.truncate(self.n);
+        picked.sort_unstable();
+
+        let mut picked = picked.into_iter().peekable();
+        Ok(cases
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, case)| {
+                if picked.peek() == Some(&idx) {
+                    picked.next();
+                    Some(case)
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint().map(|len| len.min(self.n))
+    }
+}
+
+pub struct Concat<A, B> {
+    first: A,
+    second: B,
+}
+
+#[async_trait]
+impl<A: DataSource, B: DataSource> DataSource for Concat<A, B> {
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let mut cases = self.first.load().await?;
+        cases.extend(self.second.load().await?);
+        Ok(cases)
+    }
+
+    fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+        self.first.stream().chain(self.second.stream()).boxed()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.first.size_hint()? + self.second.size_hint()?)
+    }
+}
+
+pub struct DedupeBy<S, F> {
+    inner: S,
+    key: F,
+}
+
+#[async_trait]
+impl<S, F, K> DataSource for DedupeBy<S, F>
+where
+    S: DataSource,
+    F: Fn(&TestCase) -> K + Send + Sync,
+    K: Eq + Hash + Send + 'static,
+{
+    async fn load(&self) -> Result<Vec<TestCase>> {
+        let mut seen = HashSet::new();
+        let mut cases = self.inner.load().await?;
+        cases.retain(|c| seen.insert((self.key)(c)));
+        Ok(cases)
+    }
+
+    fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+        let mut seen = HashSet::new();
+        self.inner
+            .stream()
+            .try_filter(move |c| future::ready(seen.insert((self.key)(c))))
+            .boxed()
+    }
+}
+
+/// Fisher-Yates shuffle driven by splitmix64, so results are stable across
+/// platforms and releases for a given seed.
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::VecDataSource;
+    use serde_json::json;
+
+    fn numbers(n: usize) -> VecDataSource {
+        VecDataSource::new(
+            (0..n)
+                .map(|i| TestCase::with_id(i.to_string(), json!(i), json!(i)))
+                .collect(),
+        )
+    }
+
+    fn ids(cases: &[TestCase]) -> Vec<String> {
+        cases.iter().map(|c| c.id.clone().unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_take_skip() {
+        let source = numbers(10)
+            .filter(|c| c.input.as_u64().unwrap() % 2 == 0)
+            .map(|mut c| {
+                c.input = json!(format!("n={}", c.input));
+                c
+            })
+            .skip(1)
+            .take(2);
+
+        let cases = source.load().await.unwrap();
+        assert_eq!(ids(&cases), vec!["2", "4"]);
+        assert_eq!(cases[0].input, json!("n=2"));
+
+        let streamed: Vec<TestCase> = source.stream().try_collect().await.unwrap();
+        assert_eq!(ids(&streamed), ids(&cases));
+    }
+
+    #[tokio::test]
+    async fn test_shuffle_and_sample_are_reproducible() {
+        let a = numbers(20).shuffle(7).load().await.unwrap();
+        let b = numbers(20).shuffle(7).load().await.unwrap();
+        assert_eq!(ids(&a), ids(&b));
+        assert_ne!(ids(&a), ids(&numbers(20).load().await.unwrap()));
+
+        let sample = numbers(20).sample(5, 42).load().await.unwrap();
+        assert_eq!(sample.len(), 5);
+        assert_eq!(ids(&sample), ids(&numbers(20).sample(5, 42).load().await.unwrap()));
+        let positions: Vec<u64> = sample.iter().map(|c| c.input.as_u64().unwrap()).collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[tokio::test]
+    async fn test_concat_and_dedupe() {
+        let source: Arc<dyn DataSource> = Arc::new(numbers(3));
+        let cases = source
+            .concat(numbers(5))
+            .dedupe_by(|c| c.id.clone())
+            .load()
+            .await
+            .unwrap();
+        assert_eq!(ids(&cases), vec!["0", "1", "2", "3", "4"]);
+    }
+}
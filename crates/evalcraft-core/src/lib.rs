@@ -2,12 +2,22 @@
 //! Compose data sources, a task (your agent), and scorers; run with concurrency.
 //! See `examples/simple.rs` for a quickstart.
 
+pub mod concurrency;
 pub mod config;
 pub mod datasource;
+pub mod datasource_ext;
+pub mod observer;
+pub mod policy;
+pub mod rate_limit;
 pub mod report;
+pub mod retry;
 pub mod runner;
 pub mod scorer;
+pub mod selection;
 pub mod task;
+pub mod template;
+#[cfg(test)]
+mod test_util;
 pub mod testing;
 pub mod trace;
 pub mod types;
@@ -22,11 +32,30 @@ pub mod scorers {
     pub mod sql;
 }
 
-pub use config::{EvalConfig, TaskConfig, ScorerConfig, DataConfig};
-pub use datasource::{DataSource, JsonlDataSource, VecDataSource};
+pub mod tasks {
+    pub mod cached;
+    pub mod chat;
+    pub mod command;
+    pub mod http;
+}
+
+pub use concurrency::AdaptiveConcurrency;
+pub use config::{EvalConfig, TaskConfig, ScorerConfig, ScorerEntry, PassPolicyConfig, DataConfig};
+pub use datasource::{
+    CsvDataSource, DataFormat, DataSource, FieldMapping, GlobDataSource, JsonArrayDataSource, JsonlDataSource,
+    VecDataSource, YamlDataSource,
+};
+pub use datasource_ext::DataSourceExt;
+pub use observer::{EvalObserver, JsonlObserver, ProgressObserver};
+#[cfg(feature = "persistence")]
+pub use observer::StoreObserver;
+pub use policy::{PassPolicy, ScorerOptions};
+pub use rate_limit::RateLimiter;
 pub use report::generate_html_report;
-pub use runner::{Eval, EvalBuilder};
-pub use scorer::Scorer;
+pub use retry::RetryPolicy;
+pub use runner::{fold_result, fold_summary, Eval, EvalBuilder, CANCEL_ON_CTRL_C_ENV};
+pub use scorer::{Scorer, ScoringContext};
+pub use selection::CaseSelector;
 pub use scorers::{
     contains::ContainsScorer,
     embedding::EmbeddingScorer,
@@ -36,7 +65,14 @@ pub use scorers::{
     regex::RegexScorer,
     sql::{SqlDialect, SqlScorer},
 };
-pub use task::{from_async_fn, Task};
+pub use task::{from_async_fn, from_async_fn_ctx, Task, TaskContext};
+pub use tasks::{
+    cached::{CacheMode, CachedTask},
+    chat::ChatCompletionTask,
+    command::CommandTask,
+    http::HttpTask,
+};
 pub use testing::{assert_eval_all_passed, assert_eval_avg_score, assert_eval_pass_rate};
+pub use tokio_util::sync::CancellationToken;
 pub use trace::{report_trace, Trace, TokenUsage};
-pub use types::{CaseResult, EvalResult, EvalSummary, Score, TestCase};
+pub use types::{CaseResult, CaseScore, ConcurrencySample, ErrorKind, EvalResult, EvalSummary, RunStatus, Score, SummaryAccumulator, TestCase, Trial, TrialStats, TrialSummary};
@@ -0,0 +1,293 @@
+//! Hooks for watching an eval while it runs.
+//!
+//! ```ignore
+//! let eval = Eval::builder()
+//!     .observer(Arc::new(ProgressObserver::new()))
+//!     .observer(Arc::new(JsonlObserver::create("results.jsonl")?))
+//!     /* ... */
+//!     .build()?;
+//! ```
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+
+use crate::types::{CaseResult, EvalResult, TestCase};
+
+/// Receives events from `Eval` runs. Every method defaults to doing nothing.
+///
+/// Callbacks are synchronous and may be called from several cases at once, so
+/// keep them cheap and use interior mutability for state.
+pub trait EvalObserver: Send + Sync {
+    /// `total` is the number of cases the data source reports, when it is known
+    /// up front and no case selection applies.
+    fn on_run_start(&self, _total: Option<usize>) {}
+
+    fn on_case_start(&self, _index: usize, _case: &TestCase) {}
+
+    fn on_case_finished(&self, _result: &CaseResult) {}
+
+    /// A scorer returned an error or timed out; the case gets a failed `Score` for it.
+    fn on_scorer_error(&self, _case: &TestCase, _scorer: &str, _error: &anyhow::Error) {}
+
+    /// Called once all cases are done. `Eval::run_for_each` doesn't keep results,
+    /// so there `result.cases` is empty and only the summary is set.
+    fn on_run_finished(&self, _result: &EvalResult) {}
+}
+
+/// A one-line progress bar on stderr with the running pass rate.
+#[derive(Debug, Default)]
+pub struct ProgressObserver {
+    state: Mutex<Progress>,
+}
+
+#[derive(Debug, Default)]
+struct Progress {
+    total: Option<usize>,
+    done: usize,
+    passed: usize,
+    errors: usize,
+}
+
+const BAR_WIDTH: usize = 30;
+
+impl ProgressObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn draw(progress: &Progress) {
+        let pass_rate = if progress.done == 0 { 0.0 } else { progress.passed as f64 / progress.done as f64 };
+        let bar = match progress.total {
+            Some(total) if total > 0 => {
+                let filled = (progress.done * BAR_WIDTH / total).min(BAR_WIDTH);
+                format!("[{}{}] {}/{}", "=".repeat(filled), " ".repeat(BAR_WIDTH - filled), progress.done, total)
+            }
+            _ => format!("{} done", progress.done),
+        };
+        eprint!("\r{}  pass {:.1}%  errors {}", bar, pass_rate * 100.0, progress.errors);
+        let _ = std::io::stderr().flush();
+    }
+}
+
+impl EvalObserver for ProgressObserver {
+    fn on_run_start(&self, total: Option<usize>) {
+        let mut progress = self.state.lock().unwrap();
+        *progress = Progress { total, ..Progress::default() };
+        Self::draw(&progress);
+    }
+
+    fn on_case_finished(&self, result: &CaseResult) {
+        let mut progress = self.state.lock().unwrap();
+        progress.done += 1;
+        if result.passed() {
+            progress.passed += 1;
+        }
+        if result.error.is_some() {
+            progress.errors += 1;
+        }
+        Self::draw(&progress);
+    }
+
+    fn on_run_finished(&self, _result: &EvalResult) {
+        eprintln!();
+    }
+}
+
+/// Appends each `CaseResult` to a JSON Lines file as soon as it finishes, so
+/// partial results survive a crash. Lines are in completion order.
+#[derive(Debug)]
+pub struct JsonlObserver {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl JsonlObserver {
+    /// Create (or truncate) the file at `path`.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+        Ok(Self { writer: Mutex::new(BufWriter::new(file)) })
+    }
+}
+
+impl EvalObserver for JsonlObserver {
+    fn on_case_finished(&self, result: &CaseResult) {
+        let mut writer = self.writer.lock().unwrap();
+        let written = serde_json::to_writer(&mut *writer, result)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writeln!(writer))
+            .and_then(|_| writer.flush());
+        if let Err(e) = written {
+            eprintln!("Failed to write case result: {}", e);
+        }
+    }
+}
+
+/// `evalcraft run` sets this so evals in the user's tests and examples save
+/// themselves to its database.
+pub const DB_PATH_ENV: &str = "EVALCRAFT_DB_PATH";
+/// Set by `evalcraft run --resume`: a run id, or `latest`.
+pub const RESUME_ENV: &str = "EVALCRAFT_RESUME_RUN";
+
+/// Which stored run an eval continues (see `EvalBuilder::resume_from`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resume {
+    Run(i64),
+    /// The most recent eval with the same name, in whichever run it is.
+    Latest,
+}
+
+impl Resume {
+    pub fn from_env() -> Result<Option<Self>> {
+        match std::env::var(RESUME_ENV) {
+            Ok(value) if value.trim() == "latest" => Ok(Some(Resume::Latest)),
+            Ok(value) if !value.trim().is_empty() => {
+                let run_id = value.trim().parse().with_context(|| format!("Invalid {}: {:?}", RESUME_ENV, value))?;
+                Ok(Some(Resume::Run(run_id)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Writes each case to the SQLite store as it finishes, so an interrupted or
+/// crashed run keeps its results, and fills in the eval's summary at the end.
+#[cfg(feature = "persistence")]
+#[derive(Debug)]
+pub struct StoreObserver {
+    store: evalcraft_store::Store,
+    name: String,
+    resume: Option<Resume>,
+    /// (run_id, eval_id) of the eval being written.
+    eval: Mutex<Option<(i64, i64)>>,
+}
+
+#[cfg(feature = "persistence")]
+impl StoreObserver {
+    pub fn open(db_path: impl AsRef<Path>, name: impl Into<String>) -> Result<Self> {
+        let db_path = db_path.as_ref();
+        let store = evalcraft_store::Store::open(db_path).with_context(|| format!("Failed to open store at {:?}", db_path))?;
+        Ok(Self { store, name: name.into(), resume: None, eval: Mutex::new(None) })
+    }
+
+    /// Write into an existing eval with the same name instead of a new run.
+    pub fn resume(mut self, resume: Option<Resume>) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Whether this continues a stored run (some cases may be skipped as completed).
+    pub fn is_resuming(&self) -> bool {
+        self.resume.is_some()
+    }
+
+    /// Ids of the cases that already have a result without error in the eval being resumed.
+    pub fn completed_cases(&self) -> Result<std::collections::HashSet<String>> {
+        match self.resumed_eval()? {
+            Some((_, eval_id)) => self.store.completed_case_ids(eval_id),
+            None => Ok(Default::default()),
+        }
+    }
+
+    fn resumed_eval(&self) -> Result<Option<(i64, i64)>> {
+        match self.resume {
+            None => Ok(None),
+            Some(Resume::Run(run_id)) => self.store.find_eval(&self.name, Some(run_id)),
+            Some(Resume::Latest) => self.store.find_eval(&self.name, None),
+        }
+    }
+
+    fn start_eval(&self) -> Result<(i64, i64)> {
+        if let Some(eval) = self.resumed_eval()? {
+            return Ok(eval);
+        }
+        let run_id = match self.resume {
+            Some(Resume::Run(run_id)) => run_id,
+            _ => self.store.create_run(Some(serde_json::json!({
+                "source": "implicit_persistence",
+                "eval_type": "auto"
+            })))?,
+        };
+        Ok((run_id, self.store.begin_eval(run_id, &self.name)?))
+    }
+
+    fn finish_eval(&self, run_id: i64, eval_id: i64, result: &EvalResult) -> Result<()> {
+        // The eval may hold results from earlier (resumed) runs, so summarize what's stored.
+        let cases = self.store.load_results(eval_id)?;
+        self.store.finish_eval(eval_id, &EvalResult::summarize(&cases), result.status)?;
+        if !result.concurrency.is_empty() {
+            self.store.update_run_metadata(
+                run_id,
+                &serde_json::json!({
+                    "source": "implicit_persistence",
+                    "eval_type": "auto",
+                    "concurrency": result.concurrency,
+                }),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl EvalObserver for StoreObserver {
+    fn on_run_start(&self, _total: Option<usize>) {
+        match self.start_eval() {
+            Ok(eval) => *self.eval.lock().unwrap() = Some(eval),
+            Err(e) => eprintln!("Failed to create eval in store: {}", e),
+        }
+    }
+
+    fn on_case_finished(&self, result: &CaseResult) {
+        if let Some((_, eval_id)) = *self.eval.lock().unwrap() {
+            if let Err(e) = self.store.save_case(eval_id, result) {
+                eprintln!("Failed to save case result to store: {}", e);
+            }
+        }
+    }
+
+    fn on_run_finished(&self, result: &EvalResult) {
+        if let Some((run_id, eval_id)) = self.eval.lock().unwrap().take() {
+            if let Err(e) = self.finish_eval(run_id, eval_id, result) {
+                eprintln!("Failed to save eval results to store: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_jsonl_observer_writes_each_case() {
+        let path = std::env::temp_dir().join(format!("evalcraft-observer-{}.jsonl", std::process::id()));
+        let observer = JsonlObserver::create(&path).unwrap();
+        for (index, output) in [(1, json!("b")), (0, json!("a"))] {
+            observer.on_case_finished(&CaseResult::from_trials(
+                index,
+                TestCase::new(json!("q"), json!("a")),
+                vec![crate::types::Trial {
+                    output,
+                    error: None,
+                    error_kind: None,
+                    attempts: 1,
+                    attempt_errors: Vec::new(),
+                    scores: Vec::new(),
+                    aggregate: None,
+                    traces: Vec::new(),
+                }],
+            ));
+        }
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<CaseResult> = content.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.iter().map(|cr| cr.index).collect::<Vec<_>>(), vec![1, 0]);
+        assert_eq!(lines[1].output, json!("a"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
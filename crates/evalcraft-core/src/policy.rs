@@ -0,0 +1,118 @@
+//! How a case's scores combine into one aggregate score and a pass/fail verdict.
+
+use crate::types::{CaseScore, Score};
+
+/// How much a scorer counts towards the case's aggregate score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScorerOptions {
+    weight: f64,
+    informational: bool,
+}
+
+impl Default for ScorerOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScorerOptions {
+    /// Weight 1, required.
+    pub fn new() -> Self {
+        Self { weight: 1.0, informational: false }
+    }
+
+    /// Relative weight in the aggregate score (negative weights count as 0).
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight.max(0.0);
+        self
+    }
+
+    /// Report the score but leave it out of the aggregate and the pass decision.
+    pub fn informational(mut self) -> Self {
+        self.informational = true;
+        self
+    }
+
+    pub fn is_informational(&self) -> bool {
+        self.informational
+    }
+}
+
+/// When a case passes, given its required (non-informational) scores. The
+/// aggregate score is always their weighted mean.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PassPolicy {
+    /// Every required score passed.
+    #[default]
+    All,
+    /// At least one required score passed.
+    Any,
+    /// The aggregate score is at least this value.
+    WeightedThreshold(f64),
+    /// At least this many required scores passed.
+    AtLeast(usize),
+}
+
+impl PassPolicy {
+    /// Combine `scores` (one per scorer, with `options` in the same order). Returns
+    /// `None` when there are no scores, i.e. the case wasn't scored. A case whose
+    /// scorers are all informational never passes.
+    pub fn aggregate(&self, scores: &[Score], options: &[ScorerOptions]) -> Option<CaseScore> {
+        if scores.is_empty() {
+            return None;
+        }
+        let required: Vec<(&Score, f64)> = scores
+            .iter()
+            .zip(options.iter().copied().chain(std::iter::repeat(ScorerOptions::new())))
+            .filter(|(_, o)| !o.informational)
+            .map(|(s, o)| (s, o.weight))
+            .collect();
+        if required.is_empty() {
+            return Some(CaseScore { value: 0.0, passed: false });
+        }
+
+        let total_weight: f64 = required.iter().map(|(_, w)| w).sum();
+        let value = if total_weight > 0.0 {
+            required.iter().map(|(s, w)| s.value * w).sum::<f64>() / total_weight
+        } else {
+            0.0
+        };
+        let passed_count = required.iter().filter(|(s, _)| s.passed).count();
+        let passed = match *self {
+            PassPolicy::All => passed_count == required.len(),
+            PassPolicy::Any => passed_count > 0,
+            PassPolicy::WeightedThreshold(threshold) => value >= threshold,
+            PassPolicy::AtLeast(n) => passed_count >= n,
+        };
+        Some(CaseScore { value, passed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(value: f64, passed: bool) -> Score {
+        Score { name: "s".to_string(), value, passed, details: None }
+    }
+
+    #[test]
+    fn test_pass_policies() {
+        let scores = [score(1.0, true), score(0.4, false), score(0.0, false)];
+        let options = [ScorerOptions::new().weight(3.0), ScorerOptions::new(), ScorerOptions::new().informational()];
+
+        let all = PassPolicy::All.aggregate(&scores, &options).unwrap();
+        assert!(!all.passed);
+        assert!((all.value - 0.85).abs() < 1e-9);
+        assert!(PassPolicy::Any.aggregate(&scores, &options).unwrap().passed);
+        assert!(PassPolicy::WeightedThreshold(0.8).aggregate(&scores, &options).unwrap().passed);
+        assert!(!PassPolicy::WeightedThreshold(0.9).aggregate(&scores, &options).unwrap().passed);
+        assert!(PassPolicy::AtLeast(1).aggregate(&scores, &options).unwrap().passed);
+        assert!(!PassPolicy::AtLeast(2).aggregate(&scores, &options).unwrap().passed);
+
+        // Informational failures don't fail the case.
+        let options = [ScorerOptions::new(), ScorerOptions::new().informational(), ScorerOptions::new().informational()];
+        assert_eq!(PassPolicy::All.aggregate(&scores, &options), Some(CaseScore { value: 1.0, passed: true }));
+        assert_eq!(PassPolicy::All.aggregate(&[], &[]), None);
+    }
+}
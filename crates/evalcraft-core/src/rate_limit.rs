@@ -0,0 +1,154 @@
+//! Request and token rate limiting shared across evals.
+//!
+//! ```ignore
+//! let limiter = Arc::new(RateLimiter::new().requests_per_minute(500.0)?.tokens_per_minute(200_000));
+//! let a = Eval::builder().rate_limiter(limiter.clone()) /* ... */;
+//! let b = Eval::builder().rate_limiter(limiter) /* ... */;
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tokio::time::Instant;
+
+const TOKEN_WINDOW: Duration = Duration::from_secs(60);
+
+/// Meters task calls per second and, from the `TokenUsage` of reported traces,
+/// tokens per minute. Share one limiter (via `Arc`) between every `Eval` that
+/// uses the same API key.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    request_interval: Option<Duration>,
+    tokens_per_minute: Option<u64>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next_request: Option<Instant>,
+    tokens: VecDeque<(Instant, u64)>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Space task calls evenly so at most `rps` start per second. Fails unless
+    /// `rps` is positive and large enough for the spacing to fit in a `Duration`.
+    pub fn requests_per_second(mut self, rps: f64) -> Result<Self> {
+        let interval = (rps > 0.0)
+            .then(|| Duration::try_from_secs_f64(1.0 / rps).ok())
+            .flatten()
+            .ok_or_else(|| anyhow!("Invalid request rate: {} per second (expected a positive number)", rps))?;
+        self.request_interval = Some(interval);
+        Ok(self)
+    }
+
+    pub fn requests_per_minute(self, rpm: f64) -> Result<Self> {
+        self.requests_per_second(rpm / 60.0)
+    }
+
+    /// Hold back new calls while the tokens used in the last minute reach `tpm`.
+    pub fn tokens_per_minute(mut self, tpm: u64) -> Self {
+        self.tokens_per_minute = Some(tpm);
+        self
+    }
+
+    /// Wait until a new call is allowed under both limits.
+    pub async fn acquire(&self) {
+        if let Some(limit) = self.tokens_per_minute {
+            while let Some(wait_until) = self.token_wait(limit) {
+                tokio::time::sleep_until(wait_until).await;
+            }
+        }
+
+        if let Some(interval) = self.request_interval {
+            let slot = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+                let slot = state.next_request.map_or(now, |next| next.max(now));
+                state.next_request = Some(slot + interval);
+                slot
+            };
+            tokio::time::sleep_until(slot).await;
+        }
+    }
+
+    /// Record tokens consumed by a finished call.
+    pub fn record_tokens(&self, tokens: u64) {
+        if self.tokens_per_minute.is_some() && tokens > 0 {
+            self.state.lock().unwrap().tokens.push_back((Instant::now(), tokens));
+        }
+    }
+
+    /// Tokens recorded within the last minute.
+    pub fn tokens_in_window(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        prune(&mut state.tokens);
+        state.tokens.iter().map(|(_, t)| t).sum()
+    }
+
+    /// When over the token budget, the instant the oldest usage leaves the window.
+    fn token_wait(&self, limit: u64) -> Option<Instant> {
+        let mut state = self.state.lock().unwrap();
+        prune(&mut state.tokens);
+        let used: u64 = state.tokens.iter().map(|(_, t)| t).sum();
+        if used < limit {
+            return None;
+        }
+        state.tokens.front().map(|(at, _)| *at + TOKEN_WINDOW)
+    }
+}
+
+fn prune(tokens: &mut VecDeque<(Instant, u64)>) {
+    let now = Instant::now();
+    while tokens.front().is_some_and(|(at, _)| now.duration_since(*at) >= TOKEN_WINDOW) {
+        tokens.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_per_second_spaces_calls() {
+        let limiter = RateLimiter::new().requests_per_second(50.0).unwrap();
+        let started = Instant::now();
+        for _ in 0..6 {
+            limiter.acquire().await;
+        }
+        // First call is immediate, the next five are 20ms apart.
+        assert_eq!(started.elapsed(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_invalid_request_rates() {
+        for rps in [0.0, -1.0, f64::NAN, 1e-20, f64::MIN_POSITIVE / 4.0] {
+            assert!(RateLimiter::new().requests_per_second(rps).is_err(), "{}", rps);
+        }
+        assert!(RateLimiter::new().requests_per_minute(1.0).is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tokens_per_minute_blocks_when_exhausted() {
+        let limiter = RateLimiter::new().tokens_per_minute(100);
+        limiter.acquire().await;
+        limiter.record_tokens(60);
+        limiter.acquire().await;
+        limiter.record_tokens(60);
+        assert_eq!(limiter.tokens_in_window(), 120);
+
+        let blocked = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+        assert!(blocked.is_err());
+
+        // Once the first usage leaves the window, calls go through again.
+        let started = Instant::now();
+        limiter.acquire().await;
+        assert_eq!(started.elapsed(), TOKEN_WINDOW - Duration::from_millis(50));
+        assert_eq!(limiter.tokens_in_window(), 0);
+    }
+}
@@ -5,22 +5,33 @@ pub fn generate_html_report(result: &EvalResult) -> String {
     
     for cr in &result.cases {
         let id = cr.case.id.as_deref().unwrap_or("-");
-        let all_passed = !cr.scores.is_empty() && cr.scores.iter().all(|s| s.passed);
-        let passed_icon = if all_passed { "✓" } else { "✗" };
-        let row_class = if all_passed { "pass" } else { "fail" };
-        
-        let avg = if cr.scores.is_empty() {
-            0.0
+        let passed = cr.passed();
+        let passed_icon = if passed {
+            "✓"
+        } else if cr.was_skipped() {
+            "-"
         } else {
-            let sum: f64 = cr.scores.iter().map(|s| s.value).sum();
-            sum / (cr.scores.len() as f64)
+            "✗"
         };
+        let row_class = if passed { "pass" } else { "fail" };
+        
+        let score = cr.aggregate_score().unwrap_or(0.0);
         
         let input_str = serde_json::to_string_pretty(&cr.case.input).unwrap_or_default();
         let output_str = serde_json::to_string_pretty(&cr.output).unwrap_or_default();
         let expected_str = serde_json::to_string_pretty(&cr.case.expected).unwrap_or_default();
         
         let mut scores_html = String::new();
+        for tag in &cr.case.tags {
+            scores_html.push_str(&format!(r#"<span class="badge tag">#{}</span>"#, html_escape(tag)));
+        }
+        if let Some(stats) = &cr.trial_stats {
+            let trial_class = if stats.flaky { "flaky" } else { "tag" };
+            scores_html.push_str(&format!(
+                r#"<span class="badge {}">{}/{} trials</span>"#,
+                trial_class, stats.passed, stats.trials
+            ));
+        }
         for score in &cr.scores {
             let score_class = if score.passed { "pass" } else { "fail" };
             scores_html.push_str(&format!(
@@ -30,9 +41,10 @@ pub fn generate_html_report(result: &EvalResult) -> String {
         }
         
         // Build traces HTML
-        let traces_html = if !cr.traces.is_empty() {
+        let traces: Vec<_> = cr.all_traces().collect();
+        let traces_html = if !traces.is_empty() {
             let mut traces_content = String::new();
-            for (i, trace) in cr.traces.iter().enumerate() {
+            for (i, trace) in traces.iter().enumerate() {
                 let model = trace.model.as_deref().unwrap_or("unknown");
                 let duration = trace.duration_ms.map(|d| format!("{}ms", d)).unwrap_or_else(|| "-".to_string());
                 
@@ -74,7 +86,7 @@ pub fn generate_html_report(result: &EvalResult) -> String {
             format!(
                 r#"<button class="trace-toggle" onclick="toggleTraces('trace-{}')">{} trace(s)</button>
                 <div id="trace-{}" class="trace-container" style="display: none;">{}</div>"#,
-                id, cr.traces.len(), id, traces_content
+                id, traces.len(), id, traces_content
             )
         } else {
             String::new()
@@ -95,7 +107,7 @@ pub fn generate_html_report(result: &EvalResult) -> String {
                 <td colspan="7">{}</td>
             </tr>
             "#,
-            row_class, id, passed_icon, avg, 
+            row_class, id, passed_icon, score, 
             html_escape(&input_str), 
             html_escape(&output_str), 
             html_escape(&expected_str),
@@ -106,6 +118,52 @@ pub fn generate_html_report(result: &EvalResult) -> String {
     }
     
     let pass_rate_class = if result.summary.pass_rate >= 0.8 { "good" } else if result.summary.pass_rate >= 0.5 { "warn" } else { "bad" };
+
+    let tags_html = if result.summary.by_tag.is_empty() {
+        String::new()
+    } else {
+        let mut tag_rows = String::new();
+        for (tag, t) in &result.summary.by_tag {
+            tag_rows.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td><td>{:.3}</td></tr>",
+                html_escape(tag), t.total, t.passed, t.pass_rate * 100.0, t.avg_score
+            ));
+        }
+        format!(
+            r#"<h2>By Tag</h2>
+        <table class="tags">
+            <thead>
+                <tr><th>Tag</th><th>Total</th><th>Passed</th><th>Pass Rate</th><th>Avg Score</th></tr>
+            </thead>
+            <tbody>{}</tbody>
+        </table>"#,
+            tag_rows
+        )
+    };
+
+    let trials_html = match &result.summary.trials {
+        None => String::new(),
+        Some(t) => {
+            let mut k_rows = String::new();
+            for (k, p) in &t.pass_at_k {
+                let all = t.pass_all_k.get(k).copied().unwrap_or(0.0);
+                k_rows.push_str(&format!(
+                    "<tr><td>{}</td><td>{:.1}%</td><td>{:.1}%</td></tr>",
+                    k, p * 100.0, all * 100.0
+                ));
+            }
+            format!(
+                r#"<h2>Trials ({} per case, {} flaky, mean score {:.3})</h2>
+        <table class="tags">
+            <thead>
+                <tr><th>k</th><th>pass@k</th><th>pass^k</th></tr>
+            </thead>
+            <tbody>{}</tbody>
+        </table>"#,
+                t.trials_per_case, t.flaky, t.mean_score, k_rows
+            )
+        }
+    };
     
     format!(
         r#"<!DOCTYPE html>
@@ -223,6 +281,24 @@ pub fn generate_html_report(result: &EvalResult) -> String {
             background: #f8d7da;
             color: #721c24;
         }}
+        .badge.tag {{
+            background: #e2e3e5;
+            color: #383d41;
+        }}
+        .badge.flaky {{
+            background: #fff3cd;
+            color: #856404;
+        }}
+        h2 {{
+            margin: 10px 0 0 0;
+            font-size: 18px;
+            color: #333;
+        }}
+        table.tags {{
+            width: auto;
+            min-width: 50%;
+            margin-bottom: 30px;
+        }}
         .timestamp {{
             color: #6c757d;
             font-size: 14px;
@@ -347,12 +423,15 @@ pub fn generate_html_report(result: &EvalResult) -> String {
             </div>
         </div>
         
+        {}
+        {}
+        
         <table>
             <thead>
                 <tr>
                     <th>ID</th>
                     <th>Status</th>
-                    <th>Avg Score</th>
+                    <th>Score</th>
                     <th>Input</th>
                     <th>Output</th>
                     <th>Expected</th>
@@ -373,6 +452,8 @@ pub fn generate_html_report(result: &EvalResult) -> String {
         pass_rate_class,
         result.summary.pass_rate * 100.0,
         result.summary.avg_score,
+        trials_html,
+        tags_html,
         rows
     )
 }
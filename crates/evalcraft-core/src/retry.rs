@@ -0,0 +1,235 @@
+//! Retrying transient task failures (rate limits, 5xx, timeouts) with backoff.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+type Predicate = Arc<dyn Fn(&anyhow::Error) -> bool + Send + Sync>;
+
+/// How the runner retries a failing `Task::run`.
+///
+/// Attempt `n` (1-based) waits `initial_backoff * multiplier^(n-1)`, capped at
+/// `max_backoff`, before the next try. With jitter the wait is drawn uniformly
+/// from the upper half of that range so parallel cases don't retry in lockstep.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    retry_timeouts: bool,
+    retryable: Predicate,
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .field("retry_timeouts", &self.retry_timeouts)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl RetryPolicy {
+    /// Try each case up to `max_attempts` times in total (1 disables retries).
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            retry_timeouts: true,
+            retryable: Arc::new(is_transient),
+        }
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Growth factor of the backoff between attempts (at least 1; default 2).
+    pub fn multiplier(mut self, multiplier: f64) -> anyhow::Result<Self> {
+        if !multiplier.is_finite() {
+            anyhow::bail!("Invalid backoff multiplier: {} (expected a finite number)", multiplier);
+        }
+        self.multiplier = multiplier.max(1.0);
+        Ok(self)
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Whether a case that hit `case_timeout` is tried again (default: yes).
+    /// The run deadline is never retried.
+    pub fn retry_timeouts(mut self, retry: bool) -> Self {
+        self.retry_timeouts = retry;
+        self
+    }
+
+    /// Decide which task errors are worth retrying. Defaults to `is_transient`.
+    pub fn retry_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&anyhow::Error) -> bool + Send + Sync + 'static,
+    {
+        self.retryable = Arc::new(predicate);
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub(crate) fn should_retry(&self, err: &anyhow::Error) -> bool {
+        (self.retryable)(err)
+    }
+
+    pub(crate) fn should_retry_timeout(&self) -> bool {
+        self.retry_timeouts
+    }
+
+    /// Wait before the attempt following `attempt` (1-based).
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        // Cap in f64 seconds: the uncapped backoff overflows `Duration` after a few
+        // dozen attempts.
+        let exp = self.multiplier.powi(i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX));
+        let secs = self.initial_backoff.as_secs_f64() * exp.min(f64::MAX);
+        let delay = if secs < self.max_backoff.as_secs_f64() {
+            Duration::from_secs_f64(secs)
+        } else {
+            self.max_backoff
+        };
+        if self.jitter {
+            delay.mul_f64(0.5 + 0.5 * random_unit())
+        } else {
+            delay
+        }
+    }
+}
+
+/// Default retry predicate: HTTP 408/429/5xx, connection failures and request
+/// timeouts from `reqwest`, anywhere in the error chain.
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        let Some(e) = cause.downcast_ref::<reqwest::Error>() else {
+            return false;
+        };
+        if e.is_timeout() || e.is_connect() {
+            return true;
+        }
+        e.status().is_some_and(|status| {
+            status.is_server_error()
+                || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                || status == reqwest::StatusCode::REQUEST_TIMEOUT
+        })
+    })
+}
+
+/// A uniform value in [0, 1) from the randomly keyed std hasher; good enough for jitter.
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::serve;
+    use anyhow::{anyhow, Context};
+    use serde_json::json;
+
+    /// The error `error_for_status` gives for a response with `status`.
+    async fn status_error(status: u16) -> anyhow::Error {
+        let (url, request) = serve(status, json!({})).await;
+        let response = reqwest::get(&url).await.unwrap();
+        request.await.unwrap();
+        response.error_for_status().unwrap_err().into()
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let policy = RetryPolicy::new(5)
+            .backoff(Duration::from_millis(100), Duration::from_millis(350))
+            .jitter(false);
+        let delays: Vec<u128> = (1..=4).map(|a| policy.delay(a).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 350, 350]);
+
+        let jittered = RetryPolicy::new(5).backoff(Duration::from_millis(100), Duration::from_secs(1));
+        for _ in 0..20 {
+            let d = jittered.delay(2).as_millis();
+            assert!((100..=200).contains(&d));
+        }
+    }
+
+    #[test]
+    fn test_backoff_caps_large_attempt_counts() {
+        for multiplier in [2.0, 10.0, f64::MAX] {
+            let policy = RetryPolicy::new(100).multiplier(multiplier).unwrap().jitter(false);
+            for attempt in [66, 100, 1_000, u32::MAX] {
+                assert_eq!(policy.delay(attempt), Duration::from_secs(30), "x{} attempt {}", multiplier, attempt);
+            }
+            assert!(RetryPolicy::new(100).multiplier(multiplier).unwrap().delay(u32::MAX) <= Duration::from_secs(30));
+        }
+        let zero = RetryPolicy::new(100).backoff(Duration::ZERO, Duration::from_secs(1)).jitter(false);
+        assert_eq!(zero.delay(1_000), Duration::ZERO);
+
+        for multiplier in [f64::INFINITY, f64::NAN] {
+            assert!(RetryPolicy::new(3).multiplier(multiplier).is_err());
+        }
+    }
+
+    #[test]
+    fn test_default_predicate() {
+        assert!(!is_transient(&anyhow!("wrong answer")));
+        let custom = RetryPolicy::default().retry_if(|e| e.to_string().contains("overloaded"));
+        assert!(custom.should_retry(&anyhow!("model overloaded")));
+    }
+
+    #[tokio::test]
+    async fn test_default_predicate_http_status() {
+        for status in [408, 429, 500, 503] {
+            assert!(is_transient(&status_error(status).await), "{} should be transient", status);
+        }
+        for status in [400, 401, 404] {
+            assert!(!is_transient(&status_error(status).await), "{} should not be transient", status);
+        }
+        // Found anywhere in the error chain.
+        let wrapped = Err::<(), _>(status_error(429).await).context("calling the model").unwrap_err();
+        assert!(is_transient(&wrapped));
+    }
+
+    #[tokio::test]
+    async fn test_default_predicate_connect_and_timeout() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let refused = reqwest::get(format!("http://{}", addr)).await.unwrap_err();
+        assert!(refused.is_connect());
+        assert!(is_transient(&refused.into()));
+
+        // Accepts the connection but never answers.
+        let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", silent.local_addr().unwrap());
+        let client = reqwest::Client::builder().timeout(Duration::from_millis(50)).build().unwrap();
+        let timed_out = client.get(url).send().await.unwrap_err();
+        assert!(timed_out.is_timeout());
+        assert!(is_transient(&timed_out.into()));
+    }
+}
@@ -1,18 +1,66 @@
-use std::sync::Arc;
+use std::any::Any;
+use std::collections::HashSet;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use anyhow::Result;
-use futures::stream::{self, StreamExt};
+use anyhow::{anyhow, bail, Result};
+use futures::future::{self, FutureExt};
+use futures::stream::{self, BoxStream, FusedStream, FuturesUnordered, Stream, StreamExt, TryStreamExt};
+use serde_json::Value;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
 
+use crate::concurrency::{AdaptiveConcurrency, ConcurrencyLimit};
+use crate::config::{seconds, EvalConfig};
 use crate::datasource::DataSource;
-use crate::scorer::Scorer;
-use crate::task::Task;
-use crate::types::{CaseResult, EvalResult, TestCase};
+#[cfg(feature = "persistence")]
+use crate::observer::StoreObserver;
+use crate::observer::{EvalObserver, Resume, DB_PATH_ENV};
+use crate::policy::{PassPolicy, ScorerOptions};
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use crate::scorer::{Scorer, ScoringContext};
+use crate::selection::CaseSelector;
+use crate::task::{Task, TaskContext};
+use crate::types::{CaseResult, ErrorKind, EvalResult, EvalSummary, RunStatus, Score, SummaryAccumulator, TestCase, Trial};
+
+/// Name used when persisting an eval that wasn't given one.
+const DEFAULT_NAME: &str = "Auto Eval";
+
+/// Set by `evalcraft run`: evals built while it is set are cancelled on Ctrl-C
+/// (SIGINT), as if through `EvalBuilder::cancel_token`.
+pub const CANCEL_ON_CTRL_C_ENV: &str = "EVALCRAFT_CANCEL_ON_CTRL_C";
 
 pub struct EvalBuilder {
 	data_source: Option<Arc<dyn DataSource>>,
 	task: Option<Arc<dyn Task>>,
 	scorers: Vec<Arc<dyn Scorer>>,
+	scorer_options: Vec<ScorerOptions>,
+	pass_policy: PassPolicy,
 	concurrency: usize,
+	selector: Option<CaseSelector>,
+	case_timeout: Option<Duration>,
+	scorer_timeout: Option<Duration>,
+	scorer_concurrency: Option<usize>,
+	run_deadline: Option<Duration>,
+	retry: Option<RetryPolicy>,
+	rate_limiter: Option<Arc<RateLimiter>>,
+	adaptive_concurrency: Option<AdaptiveConcurrency>,
+	trials: usize,
+	observers: Vec<Arc<dyn EvalObserver>>,
+	fail_fast: Option<usize>,
+	cancel: CancellationToken,
+	name: Option<String>,
+	db_path: Option<PathBuf>,
+	resume: Option<Resume>,
+}
+
+impl Default for EvalBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl EvalBuilder {
@@ -21,8 +69,68 @@ impl EvalBuilder {
 			data_source: None,
 			task: None,
 			scorers: Vec::new(),
+			scorer_options: Vec::new(),
+			pass_policy: PassPolicy::default(),
 			concurrency: 8,
+			selector: None,
+			case_timeout: None,
+			scorer_timeout: None,
+			scorer_concurrency: None,
+			run_deadline: None,
+			retry: None,
+			rate_limiter: None,
+			adaptive_concurrency: None,
+			trials: 1,
+			observers: Vec::new(),
+			fail_fast: None,
+			cancel: CancellationToken::new(),
+			name: None,
+			db_path: None,
+			resume: None,
+		}
+	}
+
+	/// Create a builder from a declarative `EvalConfig` (task, data and scorers).
+	pub fn from_config(config: &EvalConfig) -> Result<Self> {
+		let mut builder = Self::new()
+			.name(config.name.clone().unwrap_or_else(|| DEFAULT_NAME.to_string()))
+			.data_source(config.data.build()?)
+			.task(config.build_task()?)
+			.concurrency(config.concurrency);
+		for entry in &config.scorers {
+			builder = builder.add_scorer_with(entry.scorer.build()?, entry.options());
+		}
+		if let Some(policy) = &config.pass_policy {
+			builder = builder.pass_policy(policy.build());
+		}
+		if let Some(secs) = config.case_timeout {
+			builder = builder.case_timeout(seconds("case_timeout", secs)?);
+		}
+		if let Some(secs) = config.scorer_timeout {
+			builder = builder.scorer_timeout(seconds("scorer_timeout", secs)?);
 		}
+		if let Some(secs) = config.run_deadline {
+			builder = builder.run_deadline(seconds("run_deadline", secs)?);
+		}
+		if let Some(retry) = &config.retry {
+			builder = builder.retry(retry.build()?);
+		}
+		if let Some(rate_limit) = &config.rate_limit {
+			builder = builder.rate_limiter(Arc::new(rate_limit.build()?));
+		}
+		if let Some(adaptive) = &config.adaptive_concurrency {
+			builder = builder.adaptive_concurrency(adaptive.build());
+		}
+		if let Some(trials) = config.trials {
+			builder = builder.trials(trials);
+		}
+		if let Some(n) = config.scorer_concurrency {
+			builder = builder.scorer_concurrency(n);
+		}
+		if let Some(failures) = config.fail_fast {
+			builder = builder.fail_fast(failures);
+		}
+		Ok(builder)
 	}
 
 	pub fn data_source(mut self, data_source: Arc<dyn DataSource>) -> Self {
@@ -40,11 +148,25 @@ impl EvalBuilder {
 		I: IntoIterator<Item = Arc<dyn Scorer>>,
 	{
 		self.scorers = scorers.into_iter().collect();
+		self.scorer_options = vec![ScorerOptions::new(); self.scorers.len()];
 		self
 	}
 
-	pub fn add_scorer(mut self, scorer: Arc<dyn Scorer>) -> Self {
+	pub fn add_scorer(self, scorer: Arc<dyn Scorer>) -> Self {
+		self.add_scorer_with(scorer, ScorerOptions::new())
+	}
+
+	/// Add a scorer with a weight, or as informational only (see `ScorerOptions`).
+	pub fn add_scorer_with(mut self, scorer: Arc<dyn Scorer>, options: ScorerOptions) -> Self {
 		self.scorers.push(scorer);
+		self.scorer_options.push(options);
+		self
+	}
+
+	/// How a case's scores decide whether it passed (`PassPolicy::All` by default).
+	/// The resulting aggregate score is stored on each `CaseResult`.
+	pub fn pass_policy(mut self, policy: PassPolicy) -> Self {
+		self.pass_policy = policy;
 		self
 	}
 
@@ -53,12 +175,178 @@ impl EvalBuilder {
 		self
 	}
 
+	/// Adjust the number of in-flight cases AIMD-style instead of keeping it fixed,
+	/// starting from `concurrency`. Changes are recorded in `EvalResult::concurrency`.
+	pub fn adaptive_concurrency(mut self, adaptive: AdaptiveConcurrency) -> Self {
+		self.adaptive_concurrency = Some(adaptive);
+		self
+	}
+
+	/// Run only the cases matched by `selector` (id globs, tag expression, shard).
+	pub fn select(mut self, selector: CaseSelector) -> Self {
+		self.selector = Some(selector);
+		self
+	}
+
+	/// Give up on a case whose task hasn't returned after `timeout`. The case is
+	/// recorded with `ErrorKind::Timeout`.
+	pub fn case_timeout(mut self, timeout: Duration) -> Self {
+		self.case_timeout = Some(timeout);
+		self
+	}
+
+	/// Give up on a single scorer after `timeout`; it yields a failed `Score`
+	/// with `{"timeout": true}` in its details.
+	pub fn scorer_timeout(mut self, timeout: Duration) -> Self {
+		self.scorer_timeout = Some(timeout);
+		self
+	}
+
+	/// Run at most `n` of a case's scorers at once. By default they all run
+	/// concurrently; `1` runs them one after another.
+	pub fn scorer_concurrency(mut self, n: usize) -> Self {
+		self.scorer_concurrency = Some(n.max(1));
+		self
+	}
+
+	/// Bound the whole run. Cases still running when it elapses, and cases not
+	/// started yet, are recorded as timed out, so the run returns partial results.
+	/// Scorers still running then fail with a timeout.
+	pub fn run_deadline(mut self, deadline: Duration) -> Self {
+		self.run_deadline = Some(deadline);
+		self
+	}
+
+	/// Retry failing task calls (429s, 5xx, timeouts by default) with backoff.
+	/// Each `CaseResult` records its `attempts` and per-attempt errors.
+	pub fn retry(mut self, policy: RetryPolicy) -> Self {
+		self.retry = Some(policy);
+		self
+	}
+
+	/// Throttle task calls by requests/sec and tokens/min. Pass the same `Arc` to
+	/// several evals to share one quota.
+	pub fn rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+		self.rate_limiter = Some(limiter);
+		self
+	}
+
+	/// Run every case `n` times. Each `CaseResult` keeps all trials along with
+	/// pass@k, pass^k and a flakiness flag; the summary averages them per dataset.
+	pub fn trials(mut self, n: usize) -> Self {
+		self.trials = n.max(1);
+		self
+	}
+
+	/// Stop starting new cases once `failures` cases have failed (errored, or not
+	/// every score passed). Cases already in flight still finish; the rest are
+	/// recorded as skipped, so the run ends `Interrupted` like a cancelled one.
+	pub fn fail_fast(mut self, failures: usize) -> Self {
+		self.fail_fast = Some(failures.max(1));
+		self
+	}
+
+	/// Stop the run when `token` is cancelled: no new cases start, in-flight ones
+	/// finish (or time out), and the rest are recorded as skipped. The run then
+	/// returns the partial result with `RunStatus::Interrupted`.
+	pub fn cancel_token(mut self, token: CancellationToken) -> Self {
+		self.cancel = token;
+		self
+	}
+
+	/// Receive progress events (case started/finished, scorer errors, run finished).
+	/// Can be called several times.
+	pub fn observer(mut self, observer: Arc<dyn EvalObserver>) -> Self {
+		self.observers.push(observer);
+		self
+	}
+
+	/// Name under which results are persisted ("Auto Eval" by default). Resuming
+	/// looks the eval up by this name, so give each eval its own.
+	pub fn name(mut self, name: impl Into<String>) -> Self {
+		self.name = Some(name.into());
+		self
+	}
+
+	/// Save results to the SQLite database at `path`, case by case as they finish.
+	/// Defaults to `EVALCRAFT_DB_PATH`, which `evalcraft run` sets. Needs the
+	/// `persistence` feature.
+	pub fn persist_to(mut self, path: impl Into<PathBuf>) -> Self {
+		self.db_path = Some(path.into());
+		self
+	}
+
+	/// Continue this eval's results in stored run `run_id`: cases that already have
+	/// a result without error are skipped, the rest are run and written into the
+	/// same eval. Needs a database (see `persist_to`).
+	pub fn resume_from(mut self, run_id: i64) -> Self {
+		self.resume = Some(Resume::Run(run_id));
+		self
+	}
+
+	/// A selection from `EVALCRAFT_CASES` / `EVALCRAFT_TAGS` / `EVALCRAFT_SHARD` is
+	/// applied on top of the one given to `select`, and `EVALCRAFT_RESUME_RUN`
+	/// (from `evalcraft run --resume`) is used when `resume_from` isn't. With
+	/// `EVALCRAFT_CANCEL_ON_CTRL_C` set, Ctrl-C cancels the eval; this needs a
+	/// Tokio runtime, so build inside it.
 	pub fn build(self) -> Result<Eval> {
+		if std::env::var_os(CANCEL_ON_CTRL_C_ENV).is_some() {
+			if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+				let cancel = self.cancel.clone();
+				runtime.spawn(async move {
+					if tokio::signal::ctrl_c().await.is_ok() {
+						cancel.cancel();
+					}
+				});
+			}
+		}
+
+		let selectors = self.selector.into_iter().chain(CaseSelector::from_env()?).collect();
+		let name = self.name.unwrap_or_else(|| DEFAULT_NAME.to_string());
+		let resume = match self.resume {
+			Some(resume) => Some(resume),
+			None => Resume::from_env()?,
+		};
+		let db_path = self.db_path.or_else(|| std::env::var_os(DB_PATH_ENV).map(PathBuf::from));
+
+		#[cfg(feature = "persistence")]
+		let store = match db_path {
+			Some(path) => Some(Arc::new(StoreObserver::open(path, name.clone())?.resume(resume))),
+			None if resume.is_some() => bail!("Resuming needs a database: call persist_to or set {}", DB_PATH_ENV),
+			None => None,
+		};
+		#[cfg(not(feature = "persistence"))]
+		{
+			if resume.is_some() {
+				bail!("Resuming a run requires the 'persistence' feature");
+			}
+			if let Some(path) = db_path {
+				eprintln!("Info: {} set to {:?}, but 'persistence' feature is not enabled. Results will not be persisted.", DB_PATH_ENV, path);
+			}
+		}
+
 		Ok(Eval {
 			data_source: self.data_source.ok_or_else(|| anyhow::anyhow!("data_source must be set"))?,
 			task: self.task.ok_or_else(|| anyhow::anyhow!("task must be set"))?,
 			scorers: self.scorers,
+			scorer_options: self.scorer_options,
+			pass_policy: self.pass_policy,
 			concurrency: self.concurrency,
+			selectors,
+			case_timeout: self.case_timeout,
+			scorer_timeout: self.scorer_timeout,
+			scorer_concurrency: self.scorer_concurrency,
+			run_deadline: self.run_deadline,
+			retry: self.retry,
+			rate_limiter: self.rate_limiter,
+			adaptive_concurrency: self.adaptive_concurrency,
+			trials: self.trials,
+			observers: self.observers,
+			fail_fast: self.fail_fast,
+			cancel: self.cancel,
+			name,
+			#[cfg(feature = "persistence")]
+			store,
 		})
 	}
 }
@@ -67,7 +355,24 @@ pub struct Eval {
 	data_source: Arc<dyn DataSource>,
 	task: Arc<dyn Task>,
 	scorers: Vec<Arc<dyn Scorer>>,
+	scorer_options: Vec<ScorerOptions>,
+	pass_policy: PassPolicy,
 	concurrency: usize,
+	selectors: Vec<CaseSelector>,
+	case_timeout: Option<Duration>,
+	scorer_timeout: Option<Duration>,
+	scorer_concurrency: Option<usize>,
+	run_deadline: Option<Duration>,
+	retry: Option<RetryPolicy>,
+	rate_limiter: Option<Arc<RateLimiter>>,
+	adaptive_concurrency: Option<AdaptiveConcurrency>,
+	trials: usize,
+	observers: Vec<Arc<dyn EvalObserver>>,
+	fail_fast: Option<usize>,
+	cancel: CancellationToken,
+	name: String,
+	#[cfg(feature = "persistence")]
+	store: Option<Arc<StoreObserver>>,
 }
 
 impl Eval {
@@ -75,124 +380,1360 @@ impl Eval {
 		EvalBuilder::new()
 	}
 
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Run all cases and return the results in dataset order. With a database (see
+	/// `EvalBuilder::persist_to`), each case is also saved as it finishes. When
+	/// resuming, only the cases run this time are returned; the stored eval has all.
+	///
+	/// If the data source fails part-way (e.g. a malformed line in a JSONL file), no
+	/// new cases start and the cases already run are returned with
+	/// `RunStatus::Failed` and the error in `EvalResult::error`.
 	pub async fn run(&self) -> Result<EvalResult> {
-		let cases = self.data_source.load().await?;
-		let results = self.run_cases(cases).await?;
-		let summary = crate::types::EvalResult::summarize(&results);
-		let result = EvalResult { cases: results, summary };
-
-		// Implicit Persistence:
-		// If running under `evalcraft run`, this environment variable will be set.
-		// We automatically save the run to the database.
-		if let Ok(db_path) = std::env::var("EVALCRAFT_DB_PATH") {
-			#[cfg(feature = "persistence")]
-			{
-                // We use core::task::spawn_blocking or just run it if store is sync?
-                // Store::open is synchronous (rusqlite).
-                // We are in async context.
-                let result_clone = result.clone(); // Expensive clone? EvalResult can be large.
-                // Ideally we save refs, but `spawn_blocking` needs 'static.
-                // Let's just do it synchronously for now as it's local DB and end of run.
-                
-                match evalcraft_store::Store::open(&db_path) {
-                    Ok(store) => {
-                        // Create a run for this specific eval execution
-                        // In a real scenario, the CLI might create the "Run" and pass the ID via env var.
-                        // But for now, let's create a run per eval file execution.
-                         match store.create_run(Some(serde_json::json!({
-                            "source": "implicit_persistence",
-                            "eval_type": "auto"
-                        }))) {
-                            Ok(run_id) => {
-                                if let Err(e) = store.save_eval(run_id, "Auto Eval", &result_clone) {
-                                    eprintln!("Failed to save eval results to store: {}", e);
-                                }
-                            }
-                            Err(e) => eprintln!("Failed to create run in store: {}", e),
-                        }
-                    }
-                    Err(e) => eprintln!("Failed to open store at {}: {}", db_path, e),
-                }
-			}
-			#[cfg(not(feature = "persistence"))]
-			{
-				eprintln!("Info: EVALCRAFT_DB_PATH set to {}, but 'persistence' feature is not enabled. Results will not be persisted.", db_path);
-			}
+		let result = self.collect_cases(true).await;
+		for observer in self.observers(true) {
+			observer.on_run_finished(&result);
 		}
-
 		Ok(result)
 	}
 
+	/// Run all cases, yielding each `CaseResult` as soon as it is scored (in
+	/// completion order; see `CaseResult::index`). Cases are read lazily from
+	/// `DataSource::stream`, so memory stays bounded by `concurrency` even for very
+	/// large datasets. Fold the stream with `fold_result` or `fold_summary`, or
+	/// drop it early to stop the run, which then finishes `Interrupted`. Results
+	/// are persisted like `run`'s.
+	pub fn run_stream(&self) -> BoxStream<'_, Result<CaseResult>> {
+		stream::once(async move {
+			self.notify_run_start(true);
+			StreamFinish {
+				eval: self,
+				limit: Arc::new(Mutex::new(self.concurrency_limit())),
+				summary: Arc::new(Mutex::new(SummaryAccumulator::default())),
+				error: Arc::new(Mutex::new(None)),
+				exhausted: false,
+			}
+		})
+		.flat_map(move |mut finish| {
+			let (summary, error) = (finish.summary.clone(), finish.error.clone());
+			let results = self
+				.run_cases(self.data_source.stream(), true, finish.limit.clone())
+				.inspect_ok(move |cr| summary.lock().unwrap().add(cr))
+				.inspect_err(move |e| *error.lock().unwrap() = Some(e.to_string()))
+				.map(Some);
+			let finished = stream::once(async move {
+				finish.exhausted = true;
+				drop(finish);
+				None
+			});
+			results.chain(finished)
+		})
+		.filter_map(future::ready)
+		.boxed()
+	}
+
+	/// Like `run_stream`, handing each `CaseResult` to `on_case` instead of
+	/// yielding it, and returning the summary.
+	pub async fn run_for_each<F>(&self, mut on_case: F) -> Result<EvalSummary>
+	where
+		F: FnMut(CaseResult),
+	{
+		let mut summary = SummaryAccumulator::default();
+		let mut results = self.run_stream();
+		while let Some(cr) = results.try_next().await? {
+			summary.add(&cr);
+			on_case(cr);
+		}
+		Ok(summary.finish())
+	}
+
 	/// Run only the task for all cases, skipping scorers.
 	/// Useful for generating goldens or debugging traces/outputs.
 	pub async fn run_without_scoring(&self) -> Result<EvalResult> {
-		let cases = self.data_source.load().await?;
-		let results = self.run_cases_internal(cases, false).await?;
 		// Summary will show 0 scores but correct pass/fail based on errors
-		let summary = crate::types::EvalResult::summarize(&results);
-		Ok(EvalResult { cases: results, summary })
+		let result = self.collect_cases(false).await;
+		for observer in self.observers(false) {
+			observer.on_run_finished(&result);
+		}
+		Ok(result)
 	}
 
-	async fn run_cases(&self, cases: Vec<TestCase>) -> Result<Vec<CaseResult>> {
-		self.run_cases_internal(cases, true).await
+	/// Run every case and return the results in dataset order. A data source
+	/// error is recorded in the result rather than discarding the finished cases.
+	async fn collect_cases(&self, run_scorers: bool) -> EvalResult {
+		let limit = Arc::new(Mutex::new(self.concurrency_limit()));
+		self.notify_run_start(run_scorers);
+		let mut result = fold_cases(self.run_cases(self.data_source.stream(), run_scorers, limit.clone())).await;
+		result.concurrency = limit.lock().unwrap().take_log();
+		result
+	}
+
+	/// The registered observers, plus the store when `persist` is set (scored runs only).
+	fn observers(&self, persist: bool) -> impl Iterator<Item = &dyn EvalObserver> {
+		let observers = self.observers.iter().map(|o| o.as_ref());
+		#[cfg(feature = "persistence")]
+		let observers = observers.chain(self.store.as_deref().filter(|_| persist).map(|s| s as &dyn EvalObserver));
+		#[cfg(not(feature = "persistence"))]
+		let _ = persist;
+		observers
+	}
+
+	/// Whether completed cases of a stored eval are skipped (scored runs only).
+	fn is_resuming(&self, persist: bool) -> bool {
+		#[cfg(feature = "persistence")]
+		if let Some(store) = self.store.as_ref().filter(|_| persist) {
+			return store.is_resuming();
+		}
+		let _ = persist;
+		false
+	}
+
+	/// Ids of cases already completed in the eval being resumed.
+	fn completed_cases(&self) -> Result<HashSet<String>> {
+		#[cfg(feature = "persistence")]
+		if let Some(store) = &self.store {
+			return store.completed_cases();
+		}
+		Ok(HashSet::new())
+	}
+
+	fn notify_run_start(&self, persist: bool) {
+		// Selection and resuming may drop any number of cases, so the total is only
+		// known without them.
+		let total = if self.selectors.is_empty() && !self.is_resuming(persist) {
+			self.data_source.size_hint()
+		} else {
+			None
+		};
+		for observer in self.observers(persist) {
+			observer.on_run_start(total);
+		}
 	}
 
-	async fn run_cases_internal(&self, cases: Vec<TestCase>, run_scorers: bool) -> Result<Vec<CaseResult>> {
-		let task = self.task.clone();
-		let scorers = if run_scorers { self.scorers.clone() } else { Vec::new() };
-		
-		let stream = stream::iter(cases.into_iter()).map(move |case| {
-			let task = task.clone();
-			let scorers = scorers.clone();
+	fn concurrency_limit(&self) -> ConcurrencyLimit {
+		ConcurrencyLimit::new(self.concurrency, self.adaptive_concurrency.clone())
+	}
+
+	/// Pull selected cases from `cases` lazily, keeping up to `limit` of them in
+	/// flight. Results arrive in completion order, each tagged with its dataset index;
+	/// cases without an id get `TestCase::content_id`. A data source error is yielded
+	/// once and no new cases start after it; in-flight cases still finish. With
+	/// `fail_fast`, the cases left once enough have failed are yielded as skipped.
+	fn run_cases<'a>(
+		&'a self,
+		cases: BoxStream<'a, Result<TestCase>>,
+		run_scorers: bool,
+		limit: Arc<Mutex<ConcurrencyLimit>>,
+	) -> impl Stream<Item = Result<CaseResult>> + 'a {
+		let deadline = self.run_deadline.map(|d| Instant::now() + d);
+		let (cases, done) = match run_scorers.then(|| self.completed_cases()).transpose() {
+			Ok(done) => (cases, done.unwrap_or_default()),
+			Err(e) => (stream::iter([Err(e)]).boxed(), HashSet::new()),
+		};
+
+		let pending = cases
+			.enumerate()
+			.map(|(index, case)| -> Result<(usize, TestCase)> {
+				let mut case = case?;
+				if case.id.is_none() {
+					case.id = Some(case.content_id());
+				}
+				Ok((index, case))
+			})
+			.try_filter(move |(_, case)| {
+				let resumed = case.id.as_ref().is_some_and(|id| done.contains(id));
+				future::ready(!resumed && self.selectors.iter().all(|s| s.matches(case)))
+			})
+			.scan(false, |failed, item| {
+				let stop = std::mem::replace(failed, item.is_err());
+				future::ready((!stop).then_some(item))
+			})
+			.fuse();
+		let start = move |item: Result<(usize, TestCase)>, stopped: bool| async move {
+			let started = Instant::now();
+			let result = match item {
+				Ok((index, case)) if stopped => Ok(self.skip_case(index, case, run_scorers, "Stopped by fail_fast")),
+				Ok((index, case)) => Ok(self.run_case(index, case, run_scorers, deadline).await),
+				Err(e) => Err(e),
+			};
+			(result, started.elapsed())
+		};
+
+		// A worker pool whose size is re-read after every completion, so an adaptive
+		// limit takes effect immediately. The source is polled alongside the in-flight
+		// cases, so a slow or heavily filtered source doesn't hold them up.
+		stream::unfold((pending, FuturesUnordered::new(), 0), move |(mut pending, mut in_flight, mut failures)| {
+			let limit = limit.clone();
 			async move {
-				let (execution_result, traces) = crate::trace::scope_traces(async {
-					match task.run(&case.input).await {
-						Ok(output) => {
-							let mut scores = Vec::with_capacity(scorers.len());
-							if !scorers.is_empty() {
-								for s in &scorers {
-									match s.score(&case.expected, &output).await {
-										Ok(score) => scores.push(score),
-										Err(err) => scores.push(crate::types::Score {
-											name: s.name().to_string(),
-											value: 0.0,
-											passed: false,
-											details: Some(serde_json::json!({ "error": err.to_string() })),
-										}),
-									}
-								}
-							}
-							Ok((output, scores))
+				let stopped = self.fail_fast.is_some_and(|n| failures >= n);
+				let (result, latency) = loop {
+					let max = limit.lock().unwrap().current();
+					let can_start = !pending.is_terminated() && in_flight.len() < max;
+					if in_flight.is_empty() {
+						if !can_start {
+							return None;
+						}
+						let item = pending.next().await?;
+						in_flight.push(start(item, stopped));
+					} else if can_start {
+						tokio::select! {
+							Some(item) = pending.next() => in_flight.push(start(item, stopped)),
+							Some(done) = in_flight.next() => break done,
 						}
-						Err(e) => Err(e),
+					} else {
+						break in_flight.next().await?;
+					}
+				};
+				if let Ok(cr) = &result {
+					limit.lock().unwrap().on_complete(cr, latency);
+					if cr.error.is_some() || (run_scorers && !cr.passed()) {
+						failures += 1;
 					}
-				}).await;
-
-				match execution_result {
-					Ok((output, scores)) => CaseResult {
-						case,
-						output,
-						error: None,
-						scores,
-						traces,
-					},
-					Err(err) => CaseResult {
-						case,
-						output: serde_json::Value::Null,
-						error: Some(err.to_string()),
-						scores: Vec::new(),
-						traces,
-					},
 				}
+				Some((result, (pending, in_flight, failures)))
 			}
+		})
+	}
+
+	async fn run_case(&self, index: usize, case: TestCase, run_scorers: bool, deadline: Option<Instant>) -> CaseResult {
+		if self.cancel.is_cancelled() {
+			return self.skip_case(index, case, run_scorers, "Run cancelled");
+		}
+		for observer in self.observers(run_scorers) {
+			observer.on_case_start(index, &case);
+		}
+		let mut trials = Vec::with_capacity(self.trials);
+		for trial in 0..self.trials {
+			trials.push(self.run_trial(&case, trial, run_scorers, deadline).await);
+		}
+		let result = CaseResult::from_trials(index, case, trials);
+		for observer in self.observers(run_scorers) {
+			observer.on_case_finished(&result);
+		}
+		result
+	}
+
+	fn skip_case(&self, index: usize, case: TestCase, run_scorers: bool, reason: &str) -> CaseResult {
+		let result = CaseResult::skipped(index, case, reason);
+		for observer in self.observers(run_scorers) {
+			observer.on_case_finished(&result);
+		}
+		result
+	}
+
+	async fn run_trial(&self, case: &TestCase, trial: usize, run_scorers: bool, deadline: Option<Instant>) -> Trial {
+		let (attempts, mut traces) = crate::trace::scope_traces(self.run_task_with_retries(case, trial, deadline)).await;
+		// Traces reported by scorers (e.g. LLM judges) are kept after the task's.
+		let scores = match &attempts.result {
+			Ok((output, duration)) if run_scorers => {
+				let ctx = ScoringContext { case, output, traces: &traces, duration: *duration, trial };
+				let (scores, scorer_traces) = crate::trace::scope_traces(self.score(&ctx, deadline)).await;
+				traces.extend(scorer_traces);
+				scores
+			}
+			_ => Vec::new(),
+		};
+
+		if let Some(limiter) = &self.rate_limiter {
+			let tokens = traces.iter().filter_map(|t| t.usage.as_ref()).map(|u| u64::from(u.total_tokens));
+			limiter.record_tokens(tokens.sum());
+		}
+
+		let (output, error, error_kind) = match attempts.result {
+			Ok((output, _)) => (output, None, None),
+			Err((kind, err)) => (Value::Null, Some(err), Some(kind)),
+		};
+		let aggregate = self.pass_policy.aggregate(&scores, &self.scorer_options);
+		Trial {
+			output,
+			error,
+			error_kind,
+			attempts: attempts.count,
+			attempt_errors: attempts.errors,
+			scores,
+			aggregate,
+			traces,
+		}
+	}
+
+	/// Run the task, retrying failures the retry policy considers transient.
+	/// Backoff sleeps never extend past the run deadline, and cancelling the run
+	/// stops retrying (the last error is kept).
+	async fn run_task_with_retries(&self, case: &TestCase, trial: usize, deadline: Option<Instant>) -> Attempts {
+		let mut errors = Vec::new();
+		let mut count = 1;
+		loop {
+			let ctx = TaskContext { trial, attempt: count, cancel: self.cancel.clone(), ..TaskContext::for_case(case) };
+			let (kind, err) = match self.run_task(&case.input, ctx, deadline).await {
+				Ok(output) => return Attempts { result: Ok(output), count, errors },
+				Err(e) => e,
+			};
+			errors.push(err.to_string());
+
+			let deadline_passed = deadline.is_some_and(|d| Instant::now() >= d);
+			let policy = match &self.retry {
+				Some(policy) if count < policy.max_attempts() && !deadline_passed && !self.cancel.is_cancelled() => policy,
+				_ => return Attempts { result: Err((kind, err.to_string())), count, errors },
+			};
+			let retryable = match kind {
+				ErrorKind::Timeout => policy.should_retry_timeout(),
+				ErrorKind::Task => policy.should_retry(&err),
+				ErrorKind::Skipped => false,
+			};
+			if !retryable {
+				return Attempts { result: Err((kind, err.to_string())), count, errors };
+			}
+
+			let mut delay = policy.delay(count);
+			if let Some(d) = deadline {
+				delay = delay.min(d.saturating_duration_since(Instant::now()));
+			}
+			tokio::select! {
+				_ = tokio::time::sleep(delay) => {}
+				_ = self.cancel.cancelled() => return Attempts { result: Err((kind, err.to_string())), count, errors },
+			}
+			count += 1;
+		}
+	}
+
+	/// Run the task once, after the rate limiter allows it, under the tighter of
+	/// the case timeout and the run deadline. Waiting for the rate limiter counts
+	/// against the run deadline but not the case timeout. Returns the output with
+	/// the time the task itself took.
+	async fn run_task(
+		&self,
+		input: &Value,
+		mut ctx: TaskContext,
+		deadline: Option<Instant>,
+	) -> Result<(Value, Duration), (ErrorKind, anyhow::Error)> {
+		if let Some(limiter) = &self.rate_limiter {
+			match deadline {
+				Some(d) if tokio::time::timeout_at(d, limiter.acquire()).await.is_err() => {
+					return Err((ErrorKind::Timeout, anyhow!("Run deadline exceeded")));
+				}
+				Some(_) => {}
+				None => limiter.acquire().await,
+			}
+		}
+
+		let until_deadline = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+		if until_deadline == Some(Duration::ZERO) {
+			return Err((ErrorKind::Timeout, anyhow!("Run deadline exceeded")));
+		}
+
+		let limit = match (self.case_timeout, until_deadline) {
+			(Some(case), Some(run)) => Some(case.min(run)),
+			(case, run) => case.or(run),
+		};
+		let started = Instant::now();
+		ctx.deadline = limit.map(|limit| started + limit);
+		let run = AssertUnwindSafe(self.task.run_with_context(input, &ctx)).catch_unwind().map(|result| {
+			result.unwrap_or_else(|panic| Err(anyhow!("Task panicked: {}", panic_message(panic))))
 		});
+		let result = match limit {
+			Some(limit) => match tokio::time::timeout(limit, run).await {
+				Ok(result) => result,
+				Err(_) if until_deadline == Some(limit) => {
+					return Err((ErrorKind::Timeout, anyhow!("Run deadline exceeded")))
+				}
+				Err(_) => return Err((ErrorKind::Timeout, anyhow!("Task timed out after {:?}", limit))),
+			},
+			None => run.await,
+		};
+		result.map(|output| (output, started.elapsed())).map_err(|e| (ErrorKind::Task, e))
+	}
+
+	/// Run the scorers concurrently (up to `scorer_concurrency`), keeping their order.
+	async fn score(&self, ctx: &ScoringContext<'_>, deadline: Option<Instant>) -> Vec<Score> {
+		let limit = self.scorer_concurrency.unwrap_or(self.scorers.len()).max(1);
+		let scoring: Vec<_> = self.scorers.iter().map(|s| self.score_one(s.as_ref(), ctx, deadline)).collect();
+		stream::iter(scoring).buffered(limit).collect().await
+	}
+
+	/// A scorer that errors, times out (`scorer_timeout` or the run deadline) or
+	/// panics yields a failed `Score`.
+	async fn score_one(&self, s: &dyn Scorer, ctx: &ScoringContext<'_>, deadline: Option<Instant>) -> Score {
+		let scored = AssertUnwindSafe(s.score_with_context(ctx)).catch_unwind();
+		let until_deadline = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+		let limit = match (self.scorer_timeout, until_deadline) {
+			(Some(scorer), Some(run)) => Some(scorer.min(run)),
+			(scorer, run) => scorer.or(run),
+		};
+		let result = match limit {
+			Some(limit) => tokio::time::timeout(limit, scored).await.map_err(|_| limit),
+			None => Ok(scored.await),
+		};
+		let (err, details) = match result {
+			Ok(Ok(Ok(score))) => return score,
+			Ok(Ok(Err(err))) => {
+				let details = serde_json::json!({ "error": err.to_string() });
+				(err, details)
+			}
+			Ok(Err(panic)) => {
+				let err = anyhow!("Scorer panicked: {}", panic_message(panic));
+				let details = serde_json::json!({ "error": err.to_string(), "panic": true });
+				(err, details)
+			}
+			Err(limit) => {
+				let err = if until_deadline == Some(limit) {
+					anyhow!("Run deadline exceeded")
+				} else {
+					anyhow!("Scorer timed out after {:?}", limit)
+				};
+				let details = serde_json::json!({ "error": err.to_string(), "timeout": true });
+				(err, details)
+			}
+		};
+		for observer in self.observers(true) {
+			observer.on_scorer_error(ctx.case, s.name(), &err);
+		}
+		failed_score(s, details)
+	}
+}
+
+/// Tells the observers a `run_stream` finished when it is dropped: after its
+/// last result, or early (the run is then `Interrupted`).
+struct StreamFinish<'a> {
+	eval: &'a Eval,
+	limit: Arc<Mutex<ConcurrencyLimit>>,
+	summary: Arc<Mutex<SummaryAccumulator>>,
+	error: Arc<Mutex<Option<String>>>,
+	exhausted: bool,
+}
+
+impl Drop for StreamFinish<'_> {
+	fn drop(&mut self) {
+		let summary = self.summary.lock().unwrap().finish();
+		let error = self.error.lock().unwrap().take();
+		let status = match status(&summary, error.as_ref()) {
+			RunStatus::Completed if !self.exhausted => RunStatus::Interrupted,
+			status => status,
+		};
+		let result = EvalResult {
+			cases: Vec::new(),
+			status,
+			summary,
+			error,
+			concurrency: self.limit.lock().unwrap().take_log(),
+		};
+		for observer in self.eval.observers(true) {
+			observer.on_run_finished(&result);
+		}
+	}
+}
+
+/// Collect a `run_stream` into an `EvalResult` with the cases in dataset order.
+/// An error in the stream is recorded like `Eval::run` does. The stream doesn't
+/// carry the concurrency log, so `concurrency` is left empty.
+pub async fn fold_result<S>(results: S) -> Result<EvalResult>
+where
+	S: Stream<Item = Result<CaseResult>>,
+{
+	Ok(fold_cases(results).await)
+}
+
+async fn fold_cases<S>(results: S) -> EvalResult
+where
+	S: Stream<Item = Result<CaseResult>>,
+{
+	let mut cases = Vec::new();
+	let mut error = None;
+	futures::pin_mut!(results);
+	while let Some(item) = results.next().await {
+		match item {
+			Ok(cr) => cases.push(cr),
+			Err(e) => error = error.or(Some(e.to_string())),
+		}
+	}
+	cases.sort_by_key(|cr| cr.index);
+	let summary = EvalResult::summarize(&cases);
+	EvalResult { status: status(&summary, error.as_ref()), cases, summary, error, concurrency: Vec::new() }
+}
 
-		let results: Vec<CaseResult> = stream
-			.buffer_unordered(self.concurrency)
-			.collect()
-			.await;
-		Ok(results)
+fn status(summary: &EvalSummary, error: Option<&String>) -> RunStatus {
+	if error.is_some() {
+		RunStatus::Failed
+	} else if summary.skipped > 0 {
+		RunStatus::Interrupted
+	} else {
+		RunStatus::Completed
 	}
 }
 
+/// Fold a `run_stream` into an `EvalSummary` without keeping the results.
+pub async fn fold_summary<S>(results: S) -> Result<EvalSummary>
+where
+	S: Stream<Item = Result<CaseResult>>,
+{
+	results
+		.try_fold(SummaryAccumulator::default(), |mut summary, cr| {
+			summary.add(&cr);
+			future::ok(summary)
+		})
+		.await
+		.map(|summary| summary.finish())
+}
+
+/// The task outcome for one case, after retries.
+struct Attempts {
+	/// The output and how long the successful attempt took.
+	result: Result<(Value, Duration), (ErrorKind, String)>,
+	count: u32,
+	errors: Vec<String>,
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+	match panic.downcast::<String>() {
+		Ok(message) => *message,
+		Err(panic) => match panic.downcast::<&'static str>() {
+			Ok(message) => message.to_string(),
+			Err(_) => "unknown panic".to_string(),
+		},
+	}
+}
+
+fn failed_score(scorer: &dyn Scorer, details: Value) -> Score {
+	Score {
+		name: scorer.name().to_string(),
+		value: 0.0,
+		passed: false,
+		details: Some(details),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::datasource::VecDataSource;
+	use crate::scorers::exact::ExactMatchScorer;
+	use crate::scorers::levenshtein::LevenshteinScorer;
+	use crate::task::from_async_fn;
+	use serde_json::json;
+
+	fn echo_eval(cases: Vec<TestCase>) -> Eval {
+		Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(cases)))
+			.task(from_async_fn(|input| {
+				let input = input.clone();
+				async move { Ok(input) }
+			}))
+			.add_scorer(Arc::new(ExactMatchScorer))
+			.concurrency(2)
+			.build()
+			.unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_run_for_each_folds_summary() {
+		let eval = echo_eval(vec![
+			TestCase::with_id("a", json!(1), json!(1)),
+			TestCase::with_id("b", json!(2), json!(3)),
+			TestCase::with_id("c", json!(4), json!(4)),
+		]);
+
+		let mut seen = Vec::new();
+		let summary = eval
+			.run_for_each(|cr| seen.push(cr.case.id.unwrap()))
+			.await
+			.unwrap();
+
+		seen.sort();
+		assert_eq!(seen, vec!["a", "b", "c"]);
+		assert_eq!(summary.total, 3);
+		assert_eq!(summary.passed, 2);
+
+		let result = eval.run().await.unwrap();
+		assert_eq!(result.summary.passed, summary.passed);
+		assert_eq!(result.summary.avg_score, summary.avg_score);
+	}
+
+	/// Passes when the task made fewer than `max_calls` traced calls.
+	struct ToolCallScorer {
+		max_calls: usize,
+	}
+
+	#[async_trait::async_trait]
+	impl Scorer for ToolCallScorer {
+		fn name(&self) -> &'static str {
+			"tool_calls"
+		}
+
+		async fn score(&self, _expected: &Value, _output: &Value) -> Result<Score> {
+			bail!("tool_calls needs the scoring context")
+		}
+
+		async fn score_with_context(&self, ctx: &ScoringContext<'_>) -> Result<Score> {
+			let passed = ctx.traces.len() < self.max_calls;
+			let details = json!({
+				"input": ctx.case.input,
+				"tags": ctx.case.tags,
+				"trial": ctx.trial,
+				"slow": ctx.duration >= Duration::from_millis(20),
+			});
+			Ok(Score { name: "tool_calls".to_string(), value: f64::from(u8::from(passed)), passed, details: Some(details) })
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_scorer_sees_context() {
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(vec![
+				TestCase::with_id("few", json!(1), json!(null)).tags(["agent"]),
+				TestCase::with_id("many", json!(4), json!(null)),
+			])))
+			.task(from_async_fn(|input| {
+				let calls = input.as_u64().unwrap();
+				async move {
+					for _ in 0..calls {
+						crate::trace::report_trace(crate::trace::Trace::start_now().finish(json!(null), json!(null), None));
+					}
+					tokio::time::sleep(Duration::from_millis(20)).await;
+					Ok(json!(calls))
+				}
+			}))
+			.add_scorer(Arc::new(ToolCallScorer { max_calls: 3 }))
+			.trials(2)
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		let passed: Vec<bool> = result.cases.iter().map(CaseResult::passed).collect();
+		assert_eq!(passed, vec![true, false]);
+		let few = &result.cases[0];
+		let details = few.trials[1].scores[0].details.as_ref().unwrap();
+		assert_eq!(details, &json!({ "input": 1, "tags": ["agent"], "trial": 1, "slow": true }));
+		assert_eq!(few.trials[1].traces.len(), 1);
+		assert_eq!(few.all_traces().count(), 2);
+	}
+
+	#[tokio::test]
+	async fn test_task_sees_context() {
+		let cancel = CancellationToken::new();
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(vec![TestCase::with_id("a", json!("q"), json!("ok"))
+				.tags(["db"])
+				.metadata(json!({ "fixture": "users.db" }))])))
+			.task(crate::task::from_async_fn_ctx(|_input, ctx| {
+				let ctx = ctx.clone();
+				async move {
+					if ctx.attempt == 1 {
+						bail!("503 try again");
+					}
+					assert!(ctx.remaining().is_some_and(|r| r <= Duration::from_secs(5)));
+					assert!(!ctx.cancel.is_cancelled());
+					Ok(json!({
+						"id": ctx.case_id,
+						"tags": ctx.tags,
+						"fixture": ctx.metadata["fixture"],
+						"trial": ctx.trial,
+						"attempt": ctx.attempt,
+					}))
+				}
+			}))
+			.retry(RetryPolicy::new(2).backoff(Duration::from_millis(1), Duration::from_millis(1)).retry_if(|_| true))
+			.case_timeout(Duration::from_secs(5))
+			.cancel_token(cancel)
+			.trials(2)
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		let outputs: Vec<&Value> = result.cases[0].trials.iter().map(|t| &t.output).collect();
+		assert_eq!(outputs[0], &json!({ "id": "a", "tags": ["db"], "fixture": "users.db", "trial": 0, "attempt": 2 }));
+		assert_eq!(outputs[1]["trial"], json!(1));
+	}
+
+	#[tokio::test]
+	async fn test_pass_policy_uses_weighted_aggregate() {
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(vec![
+				TestCase::with_id("exact", json!("Hello World"), json!("Hello World")),
+				TestCase::with_id("close", json!("Hello Worlds"), json!("Hello World")),
+				TestCase::with_id("far", json!("Bye"), json!("Hello World")),
+			])))
+			.task(from_async_fn(|input| {
+				let input = input.clone();
+				async move { Ok(input) }
+			}))
+			.add_scorer(Arc::new(ExactMatchScorer))
+			.add_scorer_with(Arc::new(LevenshteinScorer::new(0.8)), ScorerOptions::new().weight(3.0))
+			.add_scorer_with(Arc::new(SlowScorer), ScorerOptions::new().informational())
+			.scorer_timeout(Duration::from_millis(10))
+			.pass_policy(PassPolicy::WeightedThreshold(0.6))
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		let passed: Vec<bool> = result.cases.iter().map(CaseResult::passed).collect();
+		assert_eq!(passed, vec![true, true, false]);
+		// The timed-out informational scorer is reported but doesn't count.
+		assert_eq!(result.cases[0].scores.len(), 3);
+		assert_eq!(result.cases[0].aggregate.unwrap().value, 1.0);
+		let close = result.cases[1].aggregate.unwrap().value;
+		assert!(close > 0.6 && close < 0.75);
+		assert_eq!(result.summary.passed, 2);
+		assert!(crate::testing::assert_eval_all_passed(&result).is_err());
+	}
+
+	#[tokio::test]
+	async fn test_summary_by_tag() {
+		let eval = echo_eval(vec![
+			TestCase::with_id("a", json!(1), json!(1)).tags(["math"]),
+			TestCase::with_id("b", json!(2), json!(3)).tags(["math", "hard"]),
+			TestCase::with_id("c", json!("x"), json!("x")).tags(["geography"]),
+			TestCase::with_id("d", json!("y"), json!("y")),
+		]);
+
+		let result = eval.run().await.unwrap();
+		let by_tag = &result.summary.by_tag;
+		assert_eq!(by_tag.len(), 3);
+		assert_eq!((by_tag["math"].total, by_tag["math"].passed), (2, 1));
+		assert_eq!(by_tag["math"].pass_rate, 0.5);
+		assert_eq!(by_tag["geography"].pass_rate, 1.0);
+		assert_eq!(by_tag["hard"].avg_score, 0.0);
+		assert!(result.summary_table().contains("By tag:"));
+	}
+
+	#[tokio::test]
+	async fn test_select_cases() {
+		let cases = vec![
+			TestCase::with_id("capital_france", json!("Paris"), json!("Paris")).tags(["geography"]),
+			TestCase::with_id("capital_peru", json!("Lima"), json!("Lima")).tags(["geography", "slow"]),
+			TestCase::with_id("sum", json!(4), json!(4)).tags(["math"]),
+		];
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(cases)))
+			.task(from_async_fn(|input| {
+				let input = input.clone();
+				async move { Ok(input) }
+			}))
+			.select(CaseSelector::new().ids("capital_*").unwrap().tags("!slow").unwrap())
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		assert_eq!(result.summary.total, 1);
+		assert_eq!(result.cases[0].case.id.as_deref(), Some("capital_france"));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_results_keep_dataset_order() {
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(
+				(0..10u64).map(|i| TestCase::new(json!(i), json!(i))).collect(),
+			)))
+			.task(from_async_fn(|input| {
+				let n = input.as_u64().unwrap();
+				async move {
+					// Earlier cases finish last
+					tokio::time::sleep(std::time::Duration::from_millis(20 - 2 * n)).await;
+					Ok(json!(n))
+				}
+			}))
+			.concurrency(10)
+			.build()
+			.unwrap();
+
+		let first = eval.run().await.unwrap();
+		let indices: Vec<usize> = first.cases.iter().map(|cr| cr.index).collect();
+		assert_eq!(indices, (0..10).collect::<Vec<_>>());
+		assert!(first.cases.iter().all(|cr| cr.case.id.as_deref().is_some_and(|id| id.starts_with("case-"))));
+
+		let second = eval.run().await.unwrap();
+		let ids = |r: &EvalResult| r.cases.iter().map(|cr| cr.case.id.clone()).collect::<Vec<_>>();
+		assert_eq!(ids(&first), ids(&second));
+	}
+
+	/// Sleeps for `input` milliseconds, then echoes it.
+	fn sleepy_eval(cases: Vec<u64>) -> EvalBuilder {
+		Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(
+				cases.into_iter().map(|ms| TestCase::new(json!(ms), json!(ms))).collect(),
+			)))
+			.task(from_async_fn(|input| {
+				let ms = input.as_u64().unwrap();
+				async move {
+					tokio::time::sleep(Duration::from_millis(ms)).await;
+					Ok(json!(ms))
+				}
+			}))
+			.add_scorer(Arc::new(ExactMatchScorer))
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_case_timeout() {
+		let eval = sleepy_eval(vec![1, 5_000]).case_timeout(Duration::from_millis(50)).build().unwrap();
+
+		let result = eval.run().await.unwrap();
+		assert!(!result.cases[0].timed_out());
+		assert!(result.cases[1].timed_out());
+		assert!(result.cases[1].error.as_deref().unwrap().contains("timed out"));
+		assert_eq!(result.summary.passed, 1);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_run_deadline_returns_partial_results() {
+		let eval = sleepy_eval(vec![1, 5_000, 5_000, 1])
+			.concurrency(1)
+			.run_deadline(Duration::from_millis(100))
+			.build()
+			.unwrap();
+
+		let started = Instant::now();
+		let result = eval.run().await.unwrap();
+		assert_eq!(started.elapsed(), Duration::from_millis(100));
+		assert_eq!(result.summary.total, 4);
+		assert_eq!(result.summary.passed, 1);
+		let timed_out: Vec<bool> = result.cases.iter().map(CaseResult::timed_out).collect();
+		assert_eq!(timed_out, vec![false, true, true, true]);
+		assert_eq!(result.cases[2].error.as_deref(), Some("Run deadline exceeded"));
+	}
+
+	struct SlowScorer;
+
+	#[async_trait::async_trait]
+	impl Scorer for SlowScorer {
+		fn name(&self) -> &'static str {
+			"slow"
+		}
+
+		async fn score(&self, _expected: &Value, _output: &Value) -> Result<Score> {
+			tokio::time::sleep(Duration::from_secs(5)).await;
+			unreachable!()
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_scorer_timeout() {
+		let eval = sleepy_eval(vec![1])
+			.add_scorer(Arc::new(SlowScorer))
+			.scorer_timeout(Duration::from_millis(50))
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		let scores = &result.cases[0].scores;
+		assert!(scores[0].passed);
+		assert!(!scores[1].passed);
+		assert_eq!(scores[1].details.as_ref().unwrap()["timeout"], json!(true));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_run_deadline_bounds_scoring() {
+		let eval = sleepy_eval(vec![1])
+			.add_scorer(Arc::new(SlowScorer))
+			.run_deadline(Duration::from_millis(100))
+			.build()
+			.unwrap();
+
+		let started = Instant::now();
+		let result = eval.run().await.unwrap();
+		assert_eq!(started.elapsed(), Duration::from_millis(100));
+		let score = &result.cases[0].scores[1];
+		assert!(!score.passed);
+		assert_eq!(score.details.as_ref().unwrap()["error"], json!("Run deadline exceeded"));
+		assert_eq!(score.details.as_ref().unwrap()["timeout"], json!(true));
+	}
+
+	struct PanickingScorer;
+
+	#[async_trait::async_trait]
+	impl Scorer for PanickingScorer {
+		fn name(&self) -> &'static str {
+			"panicky"
+		}
+
+		async fn score(&self, _expected: &Value, _output: &Value) -> Result<Score> {
+			panic!("scorer bug");
+		}
+	}
+
+	struct SleepScorer(&'static str);
+
+	#[async_trait::async_trait]
+	impl Scorer for SleepScorer {
+		fn name(&self) -> &'static str {
+			self.0
+		}
+
+		async fn score(&self, _expected: &Value, _output: &Value) -> Result<Score> {
+			tokio::time::sleep(Duration::from_millis(200)).await;
+			Ok(Score { name: self.0.to_string(), value: 1.0, passed: true, details: None })
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_scorers_run_concurrently() {
+		let eval = sleepy_eval(vec![1])
+			.add_scorer(Arc::new(SleepScorer("a")))
+			.add_scorer(Arc::new(SleepScorer("b")))
+			.add_scorer(Arc::new(SleepScorer("c")))
+			.build()
+			.unwrap();
 
+		// The clock is paused, so this is the sum of the sleeps: the task's 1ms plus
+		// 200ms for all three scorers at once (600ms if they ran one by one).
+		let started = Instant::now();
+		let result = eval.run().await.unwrap();
+		assert_eq!(started.elapsed(), Duration::from_millis(201));
+		let names: Vec<&str> = result.cases[0].scores.iter().map(|s| s.name.as_str()).collect();
+		assert_eq!(names, vec!["exact_match", "a", "b", "c"]);
+	}
+
+	#[tokio::test]
+	async fn test_panics_are_isolated() {
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(vec![
+				TestCase::with_id("ok", json!("ok"), json!("ok")),
+				TestCase::with_id("boom", json!("boom"), json!("boom")),
+			])))
+			.task(from_async_fn(|input| {
+				let input = input.clone();
+				async move {
+					if input == json!("boom") {
+						panic!("task bug");
+					}
+					Ok(input)
+				}
+			}))
+			.add_scorer(Arc::new(ExactMatchScorer))
+			.add_scorer(Arc::new(PanickingScorer))
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		let ok = &result.cases[0];
+		assert!(ok.scores[0].passed);
+		assert!(!ok.scores[1].passed);
+		assert_eq!(ok.scores[1].details.as_ref().unwrap()["panic"], json!(true));
+		assert!(ok.scores[1].details.as_ref().unwrap()["error"].as_str().unwrap().contains("scorer bug"));
+		assert_eq!(result.cases[1].error.as_deref(), Some("Task panicked: task bug"));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_retry_transient_failures() {
+		let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+		let counter = calls.clone();
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(vec![
+				TestCase::with_id("flaky", json!("flaky"), json!("ok")),
+				TestCase::with_id("wrong", json!("wrong"), json!("ok")),
+			])))
+			.task(from_async_fn(move |input| {
+				let input = input.clone();
+				let n = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+				async move {
+					match input.as_str() {
+						Some("flaky") if n < 2 => Err(anyhow!("503 overloaded")),
+						Some("flaky") => Ok(json!("ok")),
+						_ => Err(anyhow!("bad request")),
+					}
+				}
+			}))
+			.add_scorer(Arc::new(ExactMatchScorer))
+			.concurrency(1)
+			.retry(
+				RetryPolicy::new(3)
+					.backoff(Duration::from_millis(1), Duration::from_millis(5))
+					.retry_if(|e| e.to_string().starts_with("503")),
+			)
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		let flaky = &result.cases[0];
+		assert!(flaky.error.is_none());
+		assert_eq!(flaky.attempts, 3);
+		assert_eq!(flaky.attempt_errors, vec!["503 overloaded", "503 overloaded"]);
+
+		let wrong = &result.cases[1];
+		assert_eq!(wrong.attempts, 1);
+		assert_eq!(wrong.error.as_deref(), Some("bad request"));
+		assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_cancel_stops_retrying() {
+		let cancel = CancellationToken::new();
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(vec![TestCase::new(json!("q"), json!("a"))])))
+			.task(from_async_fn(|_| async { Err(anyhow!("503 overloaded")) }))
+			.retry(RetryPolicy::new(5).backoff(Duration::from_secs(10), Duration::from_secs(60)).retry_if(|_| true))
+			.cancel_token(cancel.clone())
+			.build()
+			.unwrap();
+
+		let run = tokio::spawn(async move {
+			let started = Instant::now();
+			let result = eval.run().await;
+			(result, started.elapsed())
+		});
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		cancel.cancel();
+		let (result, elapsed) = run.await.unwrap();
+		let result = result.unwrap();
+		// Stopped in the first 10s backoff instead of sitting out the schedule.
+		assert_eq!(elapsed, Duration::from_millis(50));
+		assert_eq!(result.cases[0].attempts, 1);
+		assert_eq!(result.cases[0].error.as_deref(), Some("503 overloaded"));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_rate_limiter_meters_tokens_across_evals() {
+		let limiter = Arc::new(RateLimiter::new().tokens_per_minute(100));
+		let build = || {
+			Eval::builder()
+				.data_source(Arc::new(VecDataSource::new(vec![TestCase::new(json!("q"), json!("a"))])))
+				.task(from_async_fn(|_| async {
+					crate::trace::report_trace(crate::trace::Trace::start_now().finish(
+						json!("q"),
+						json!("a"),
+						Some(crate::trace::TokenUsage { input_tokens: 80, output_tokens: 40, total_tokens: 120 }),
+					));
+					Ok(json!("a"))
+				}))
+				.rate_limiter(limiter.clone())
+				.run_deadline(Duration::from_millis(100))
+				.build()
+				.unwrap()
+		};
+
+		let first = build().run().await.unwrap();
+		assert!(first.cases[0].error.is_none());
+		assert_eq!(limiter.tokens_in_window(), 120);
+
+		// The shared budget is spent, so the second eval can't start before its deadline.
+		let second = build().run().await.unwrap();
+		assert!(second.cases[0].timed_out());
+	}
+
+	#[tokio::test]
+	async fn test_run_stream_folds_into_result() {
+		let eval = echo_eval(vec![
+			TestCase::with_id("a", json!(1), json!(1)),
+			TestCase::with_id("b", json!(2), json!(3)),
+			TestCase::with_id("c", json!(3), json!(3)),
+		]);
+
+		let result = fold_result(eval.run_stream()).await.unwrap();
+		assert_eq!(result.cases.iter().map(|cr| cr.index).collect::<Vec<_>>(), vec![0, 1, 2]);
+		assert_eq!((result.summary.total, result.summary.passed), (3, 2));
+
+		let summary = fold_summary(eval.run_stream()).await.unwrap();
+		assert_eq!((summary.total, summary.passed), (3, 2));
+	}
+
+	#[tokio::test]
+	async fn test_fail_fast_stops_starting_cases() {
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(
+				(0..20).map(|i| TestCase::new(json!(i), json!(-1))).collect(),
+			)))
+			.task(from_async_fn(|input| {
+				let input = input.clone();
+				async move { Ok(input) }
+			}))
+			.add_scorer(Arc::new(ExactMatchScorer))
+			.concurrency(1)
+			.fail_fast(2)
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		assert_eq!(result.summary.total, 2);
+		assert_eq!(result.summary.passed, 0);
+		assert_eq!(result.summary.skipped, 18);
+		assert_eq!(result.status, RunStatus::Interrupted);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_cancel_skips_remaining_cases() {
+		let cancel = CancellationToken::new();
+		let eval = sleepy_eval(vec![1, 100, 100, 100, 100]).concurrency(2).cancel_token(cancel.clone()).build().unwrap();
+
+		let run = tokio::spawn(async move { eval.run().await });
+		tokio::time::sleep(Duration::from_millis(30)).await;
+		cancel.cancel();
+		let result = run.await.unwrap().unwrap();
+
+		assert_eq!(result.status, RunStatus::Interrupted);
+		assert_eq!(result.cases.len(), 5);
+		// Cases 0-2 had started and finish normally; the rest never run.
+		let skipped: Vec<bool> = result.cases.iter().map(CaseResult::was_skipped).collect();
+		assert_eq!(skipped, vec![false, false, false, true, true]);
+		assert_eq!((result.summary.total, result.summary.passed, result.summary.skipped), (3, 3, 2));
+	}
+
+	/// Yields its first case at once and the rest after a long pause.
+	struct SlowSource;
+
+	#[async_trait::async_trait]
+	impl DataSource for SlowSource {
+		async fn load(&self) -> Result<Vec<TestCase>> {
+			self.stream().try_collect().await
+		}
+
+		fn stream(&self) -> BoxStream<'_, Result<TestCase>> {
+			stream::iter(0..2u64)
+				.then(|i| async move {
+					if i > 0 {
+						tokio::time::sleep(Duration::from_secs(10)).await;
+					}
+					Ok(TestCase::new(json!(10), json!(10)))
+				})
+				.boxed()
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_slow_source_does_not_stall_cases() {
+		let eval = sleepy_eval(Vec::new()).data_source(Arc::new(SlowSource)).build().unwrap();
+
+		let started = Instant::now();
+		let mut results = eval.run_stream();
+		let first = results.try_next().await.unwrap().unwrap();
+		assert_eq!(first.index, 0);
+		assert_eq!(started.elapsed(), Duration::from_millis(10));
+		assert_eq!(results.try_next().await.unwrap().unwrap().index, 1);
+		assert_eq!(started.elapsed(), Duration::from_millis(10_010));
+	}
+
+	#[tokio::test]
+	async fn test_data_source_error_keeps_finished_cases() {
+		let path = std::env::temp_dir().join(format!("evalcraft-malformed-{}.jsonl", std::process::id()));
+		std::fs::write(&path, "{\"input\":1,\"expected\":1}\nnot json\n{\"input\":3,\"expected\":3}\n").unwrap();
+		let observer = Arc::new(RecordingObserver::default());
+		let eval = Eval::builder()
+			.data_source(Arc::new(crate::datasource::JsonlDataSource::new(path.clone())))
+			.task(from_async_fn(|input| {
+				let input = input.clone();
+				async move { Ok(input) }
+			}))
+			.add_scorer(Arc::new(ExactMatchScorer))
+			.observer(observer.clone())
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		assert_eq!(result.status, RunStatus::Failed);
+		assert!(result.error.as_deref().unwrap().contains("line 2"), "{:?}", result.error);
+		assert_eq!(result.cases.len(), 1);
+		assert_eq!((result.summary.total, result.summary.passed), (1, 1));
+		assert_eq!(observer.events.lock().unwrap().last().unwrap(), "finished 1 failed");
+		assert!(crate::testing::assert_eval_all_passed(&result).is_err());
+		std::fs::remove_file(path).unwrap();
+	}
+
+	#[cfg(feature = "persistence")]
+	#[tokio::test]
+	async fn test_resume_runs_missing_and_errored_cases() {
+		let db = std::env::temp_dir().join(format!("evalcraft-resume-{}.db", std::process::id()));
+		let fail = Arc::new(std::sync::atomic::AtomicBool::new(true));
+		let observer = Arc::new(RecordingObserver::default());
+		let build = |resume: Option<i64>| {
+			let fail = fail.clone();
+			let builder = Eval::builder()
+				.name("resume")
+				.persist_to(&db)
+				.observer(observer.clone())
+				.data_source(Arc::new(VecDataSource::new(
+					(0..4).map(|i| TestCase::with_id(format!("c{}", i), json!(i), json!(i))).collect(),
+				)))
+				.task(from_async_fn(move |input| {
+					let input = input.clone();
+					let fail = fail.load(std::sync::atomic::Ordering::SeqCst);
+					async move {
+						match input.as_u64() {
+							Some(1) if fail => Err(anyhow!("503")),
+							_ => Ok(input),
+						}
+					}
+				}))
+				.add_scorer(Arc::new(ExactMatchScorer));
+			match resume {
+				Some(run_id) => builder.resume_from(run_id),
+				None => builder,
+			}
+			.build()
+			.unwrap()
+		};
+
+		let first = build(None).run().await.unwrap();
+		assert_eq!(first.summary.passed, 3);
+		let store = evalcraft_store::Store::open(&db).unwrap();
+		let (run_id, eval_id) = store.find_eval("resume", None).unwrap().unwrap();
+
+		fail.store(false, std::sync::atomic::Ordering::SeqCst);
+		let second = build(Some(run_id)).run().await.unwrap();
+		assert_eq!(second.cases.iter().map(|cr| cr.case.id.as_deref()).collect::<Vec<_>>(), vec![Some("c1")]);
+		// Only the first run knows how many cases it will run.
+		let starts: Vec<String> =
+			observer.events.lock().unwrap().iter().filter(|e| e.starts_with("start")).cloned().collect();
+		assert_eq!(starts, vec!["start Some(4)", "start None"]);
+
+		assert_eq!(store.find_eval("resume", None).unwrap(), Some((run_id, eval_id)));
+		let stored = store.load_results(eval_id).unwrap();
+		assert_eq!(stored.len(), 4);
+		assert!(stored.iter().all(|cr| cr.error.is_none()));
+		std::fs::remove_file(db).unwrap();
+	}
+
+	#[derive(Default)]
+	struct RecordingObserver {
+		events: Mutex<Vec<String>>,
+	}
+
+	impl EvalObserver for RecordingObserver {
+		fn on_run_start(&self, total: Option<usize>) {
+			self.events.lock().unwrap().push(format!("start {:?}", total));
+		}
+
+		fn on_case_start(&self, index: usize, _case: &TestCase) {
+			self.events.lock().unwrap().push(format!("case {}", index));
+		}
+
+		fn on_case_finished(&self, result: &CaseResult) {
+			self.events.lock().unwrap().push(format!("done {}", result.index));
+		}
+
+		fn on_scorer_error(&self, _case: &TestCase, scorer: &str, error: &anyhow::Error) {
+			self.events.lock().unwrap().push(format!("{}: {}", scorer, error));
+		}
+
+		fn on_run_finished(&self, result: &EvalResult) {
+			self.events.lock().unwrap().push(format!("finished {} {}", result.summary.total, result.status.as_str()));
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_observer_events() {
+		let observer = Arc::new(RecordingObserver::default());
+		let eval = sleepy_eval(vec![1, 1])
+			.add_scorer(Arc::new(SlowScorer))
+			.scorer_timeout(Duration::from_millis(20))
+			.concurrency(1)
+			.observer(observer.clone())
+			.build()
+			.unwrap();
+
+		eval.run().await.unwrap();
+		let events = observer.events.lock().unwrap().clone();
+		assert_eq!(
+			events,
+			vec![
+				"start Some(2)",
+				"case 0",
+				"slow: Scorer timed out after 20ms",
+				"done 0",
+				"case 1",
+				"slow: Scorer timed out after 20ms",
+				"done 1",
+				"finished 2 completed",
+			]
+		);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_dropped_run_stream_finishes_interrupted() {
+		let observer = Arc::new(RecordingObserver::default());
+		let eval = sleepy_eval(vec![1, 100, 100]).concurrency(1).observer(observer.clone()).build().unwrap();
+
+		let mut results = eval.run_stream();
+		assert_eq!(results.try_next().await.unwrap().unwrap().index, 0);
+		drop(results);
+		assert_eq!(observer.events.lock().unwrap().last().unwrap(), "finished 1 interrupted");
+	}
+
+	#[tokio::test]
+	async fn test_trials_pass_at_k_and_flakiness() {
+		let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+		let counter = calls.clone();
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(vec![
+				TestCase::with_id("stable", json!("stable"), json!("ok")),
+				TestCase::with_id("flaky", json!("flaky"), json!("ok")),
+			])))
+			.task(from_async_fn(move |input| {
+				let stable = input == &json!("stable");
+				let n = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+				// The flaky case passes on 1 of its 4 trials.
+				async move { Ok(if stable || n == 4 { json!("ok") } else { json!("nope") }) }
+			}))
+			.add_scorer(Arc::new(ExactMatchScorer))
+			.concurrency(1)
+			.trials(4)
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 8);
+
+		let stable = result.cases[0].trial_stats.as_ref().unwrap();
+		assert_eq!((stable.trials, stable.passed, stable.flaky), (4, 4, false));
+		assert_eq!(stable.pass_all_k[&4], 1.0);
+
+		let flaky = &result.cases[1];
+		assert_eq!(flaky.trials.len(), 4);
+		let stats = flaky.trial_stats.as_ref().unwrap();
+		assert_eq!((stats.passed, stats.flaky), (1, true));
+		assert!((stats.pass_at_k[&1] - 0.25).abs() < 1e-9);
+		assert!((stats.pass_at_k[&2] - 0.5).abs() < 1e-9);
+		assert_eq!(stats.pass_at_k[&4], 1.0);
+		assert_eq!(stats.pass_all_k[&2], 0.0);
+
+		// Only the first trial passed, so the case as a whole doesn't.
+		assert!(flaky.trials[0].passed());
+		assert!(!flaky.passed());
+		assert_eq!(flaky.aggregate_score(), Some(0.25));
+		assert_eq!((flaky.scores[0].value, flaky.scores[0].passed), (0.25, false));
+		assert!(flaky.traces.is_empty());
+		assert!(result.cases[0].passed());
+		assert_eq!(result.summary.passed, 1);
+
+		let summary = result.summary.trials.as_ref().unwrap();
+		assert_eq!((summary.trials_per_case, summary.flaky), (4, 1));
+		assert!((summary.pass_at_k[&1] - 0.625).abs() < 1e-9);
+		assert!((summary.pass_all_k[&1] - 0.625).abs() < 1e-9);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn test_adaptive_concurrency_backs_off_on_errors() {
+		let in_flight = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+		let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+		let (counter, max_seen) = (in_flight.clone(), peak.clone());
+		let eval = Eval::builder()
+			.data_source(Arc::new(VecDataSource::new(
+				(0..60u64).map(|i| TestCase::new(json!(i), json!(i))).collect(),
+			)))
+			.task(from_async_fn(move |input| {
+				let n = input.as_u64().unwrap();
+				let (counter, max_seen) = (counter.clone(), max_seen.clone());
+				async move {
+					let now = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+					max_seen.fetch_max(now, std::sync::atomic::Ordering::SeqCst);
+					tokio::time::sleep(Duration::from_millis(2)).await;
+					counter.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+					// Everything after case 40 is throttled
+					if n >= 40 {
+						Err(anyhow!("429 Too Many Requests"))
+					} else {
+						Ok(json!(n))
+					}
+				}
+			}))
+			.concurrency(2)
+			.adaptive_concurrency(AdaptiveConcurrency::new(1, 6))
+			.build()
+			.unwrap();
+
+		let result = eval.run().await.unwrap();
+		assert_eq!(result.summary.total, 60);
+		assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 6);
+
+		// Grows to the max while cases succeed, then backs off once they're throttled.
+		let limits: Vec<usize> = result.concurrency.iter().map(|s| s.limit).collect();
+		assert_eq!(limits, vec![2, 3, 4, 5, 6, 3, 1]);
+	}
+}
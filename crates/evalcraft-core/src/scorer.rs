@@ -1,11 +1,34 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
 
-use crate::types::Score;
+use crate::trace::Trace;
+use crate::types::{Score, TestCase};
+
+/// Everything the runner knows about a case when it scores one trial.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoringContext<'a> {
+    /// The case, with its input, expected value, id, tags and metadata.
+    pub case: &'a TestCase,
+    pub output: &'a Value,
+    /// Traces the task reported while producing `output`.
+    pub traces: &'a [Trace],
+    /// How long the successful task call took (excluding retries and rate-limit waits).
+    pub duration: Duration,
+    /// 0-based trial index (see `EvalBuilder::trials`).
+    pub trial: usize,
+}
 
 #[async_trait]
 pub trait Scorer: Send + Sync {
     fn name(&self) -> &'static str;
     async fn score(&self, expected: &Value, output: &Value) -> Result<Score>;
+
+    /// Score with access to the whole case, traces and latency. The runner calls
+    /// this; the default compares `expected` and `output` with `score`.
+    async fn score_with_context(&self, ctx: &ScoringContext<'_>) -> Result<Score> {
+        self.score(&ctx.case.expected, ctx.output).await
+    }
 }
@@ -18,7 +18,7 @@ pub enum SqlDialect {
 }
 
 impl SqlDialect {
-    fn to_dialect(&self) -> Box<dyn Dialect> {
+    fn to_dialect(self) -> Box<dyn Dialect> {
         match self {
             SqlDialect::Generic => Box::new(GenericDialect {}),
             SqlDialect::PostgreSQL => Box::new(PostgreSqlDialect {}),
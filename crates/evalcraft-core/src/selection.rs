@@ -0,0 +1,277 @@
+//! Choosing which cases of a dataset to run: id globs, tag expressions and shards.
+//!
+//! ```ignore
+//! let selector = CaseSelector::new()
+//!     .ids("capital_*")?
+//!     .tags("math && !slow")?
+//!     .shard(2, 8)?;
+//! let eval = Eval::builder().select(selector) /* ... */;
+//! ```
+//!
+//! The same selection can be given through `EVALCRAFT_CASES`, `EVALCRAFT_TAGS` and
+//! `EVALCRAFT_SHARD` (as set by `evalcraft run --cases/--tags/--shard`).
+
+use anyhow::{anyhow, bail, Result};
+use glob::Pattern;
+
+use crate::types::{stable_hash, TestCase};
+
+pub const CASES_ENV: &str = "EVALCRAFT_CASES";
+pub const TAGS_ENV: &str = "EVALCRAFT_TAGS";
+pub const SHARD_ENV: &str = "EVALCRAFT_SHARD";
+
+/// A filter over test cases. A case is selected when it matches every configured
+/// criterion; an empty selector selects everything.
+#[derive(Debug, Clone, Default)]
+pub struct CaseSelector {
+    ids: Vec<Pattern>,
+    tags: Option<TagExpr>,
+    shard: Option<(u64, u64)>,
+}
+
+impl CaseSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep cases whose id matches one of the comma-separated globs (`capital_*,math/*`).
+    /// Cases without an id never match.
+    pub fn ids(mut self, patterns: &str) -> Result<Self> {
+        for pattern in patterns.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            self.ids
+                .push(Pattern::new(pattern).map_err(|e| anyhow!("Invalid case pattern '{}': {}", pattern, e))?);
+        }
+        Ok(self)
+    }
+
+    /// Keep cases whose tags satisfy a boolean expression such as `math && !slow`
+    /// or `(easy || medium) && !flaky`.
+    pub fn tags(mut self, expr: &str) -> Result<Self> {
+        self.tags = Some(TagExpr::parse(expr)?);
+        Ok(self)
+    }
+
+    /// Keep the `index`-th of `total` shards (1-based, so `shard(2, 8)` is "shard 2/8").
+    /// Cases are assigned by a stable hash of their id (see `TestCase::content_id`).
+    pub fn shard(mut self, index: u64, total: u64) -> Result<Self> {
+        if total == 0 || index == 0 || index > total {
+            bail!("Invalid shard {}/{}: expected 1 <= index <= total", index, total);
+        }
+        self.shard = Some((index, total));
+        Ok(self)
+    }
+
+    /// Parse a shard spec of the form `index/total`, e.g. `2/8`.
+    pub fn shard_spec(self, spec: &str) -> Result<Self> {
+        let (index, total) = spec
+            .split_once('/')
+            .and_then(|(i, t)| Some((i.trim().parse().ok()?, t.trim().parse().ok()?)))
+            .ok_or_else(|| anyhow!("Invalid shard '{}': expected index/total, e.g. 2/8", spec))?;
+        self.shard(index, total)
+    }
+
+    /// Build a selector from `EVALCRAFT_CASES`, `EVALCRAFT_TAGS` and `EVALCRAFT_SHARD`.
+    /// Returns `None` when none of them is set.
+    pub fn from_env() -> Result<Option<Self>> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+        let (cases, tags, shard) = (var(CASES_ENV), var(TAGS_ENV), var(SHARD_ENV));
+        if cases.is_none() && tags.is_none() && shard.is_none() {
+            return Ok(None);
+        }
+
+        let mut selector = Self::new();
+        if let Some(cases) = cases {
+            selector = selector.ids(&cases)?;
+        }
+        if let Some(tags) = tags {
+            selector = selector.tags(&tags)?;
+        }
+        if let Some(shard) = shard {
+            selector = selector.shard_spec(&shard)?;
+        }
+        Ok(Some(selector))
+    }
+
+    pub fn matches(&self, case: &TestCase) -> bool {
+        if !self.ids.is_empty() {
+            match case.id.as_deref() {
+                Some(id) if self.ids.iter().any(|p| p.matches(id)) => {}
+                _ => return false,
+            }
+        }
+        if let Some(expr) = &self.tags {
+            if !expr.eval(&case.tags) {
+                return false;
+            }
+        }
+        if let Some((index, total)) = self.shard {
+            let key = match &case.id {
+                Some(id) => stable_hash(id.as_bytes()),
+                None => stable_hash(case.content_id().as_bytes()),
+            };
+            if key % total != index - 1 {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TagExpr {
+    Tag(String),
+    Not(Box<TagExpr>),
+    And(Box<TagExpr>, Box<TagExpr>),
+    Or(Box<TagExpr>, Box<TagExpr>),
+}
+
+impl TagExpr {
+    fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0 };
+        let expr = parser.or()?;
+        if parser.pos != tokens.len() {
+            bail!("Unexpected '{}' in tag expression '{}'", tokens[parser.pos], input);
+        }
+        Ok(expr)
+    }
+
+    fn eval(&self, tags: &[String]) -> bool {
+        match self {
+            TagExpr::Tag(tag) => tags.iter().any(|t| t == tag),
+            TagExpr::Not(inner) => !inner.eval(tags),
+            TagExpr::And(a, b) => a.eval(tags) && b.eval(tags),
+            TagExpr::Or(a, b) => a.eval(tags) || b.eval(tags),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' | ')' | '!' => tokens.push(c.to_string()),
+            '&' | '|' => {
+                if chars.next() != Some(c) {
+                    bail!("Expected '{}{}' in tag expression '{}'", c, c, input);
+                }
+                tokens.push(format!("{}{}", c, c));
+            }
+            c if is_tag_char(c) => {
+                let mut tag = c.to_string();
+                while let Some(&next) = chars.peek().filter(|n| is_tag_char(**n)) {
+                    tag.push(next);
+                    chars.next();
+                }
+                tokens.push(tag);
+            }
+            other => bail!("Unexpected character '{}' in tag expression '{}'", other, input),
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '/')
+}
+
+/// Recursive descent: `||` binds loosest, then `&&`, then `!`.
+struct Parser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn or(&mut self) -> Result<TagExpr> {
+        let mut expr = self.and()?;
+        while self.peek() == Some("||") {
+            self.pos += 1;
+            expr = TagExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<TagExpr> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some("&&") {
+            self.pos += 1;
+            expr = TagExpr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<TagExpr> {
+        let token = self.peek().ok_or_else(|| anyhow!("Unexpected end of tag expression"))?;
+        self.pos += 1;
+        match token {
+            "!" => Ok(TagExpr::Not(Box::new(self.unary()?))),
+            "(" => {
+                let expr = self.or()?;
+                if self.peek() != Some(")") {
+                    bail!("Missing ')' in tag expression");
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            ")" | "&&" | "||" => bail!("Unexpected '{}' in tag expression", token),
+            tag => Ok(TagExpr::Tag(tag.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn case(id: &str, tags: &[&str]) -> TestCase {
+        TestCase::with_id(id, json!(null), json!(null)).tags(tags.iter().copied())
+    }
+
+    #[test]
+    fn test_id_globs() {
+        let selector = CaseSelector::new().ids("capital_*, math/1").unwrap();
+        assert!(selector.matches(&case("capital_france", &[])));
+        assert!(selector.matches(&case("math/1", &[])));
+        assert!(!selector.matches(&case("math/2", &[])));
+        assert!(!selector.matches(&TestCase::new(json!(1), json!(1))));
+    }
+
+    #[test]
+    fn test_tag_expressions() {
+        let selector = CaseSelector::new().tags("math && !slow").unwrap();
+        assert!(selector.matches(&case("a", &["math"])));
+        assert!(!selector.matches(&case("b", &["math", "slow"])));
+        assert!(!selector.matches(&case("c", &["geography"])));
+
+        let selector = CaseSelector::new().tags("(easy || medium) && !flaky").unwrap();
+        assert!(selector.matches(&case("a", &["medium"])));
+        assert!(!selector.matches(&case("b", &["easy", "flaky"])));
+
+        assert!(CaseSelector::new().tags("math &&").is_err());
+        assert!(CaseSelector::new().tags("math & slow").is_err());
+        assert!(CaseSelector::new().tags("(math").is_err());
+    }
+
+    #[test]
+    fn test_shards_partition_cases() {
+        let cases: Vec<TestCase> = (0..100).map(|i| case(&format!("case_{}", i), &[])).collect();
+        let mut counts = Vec::new();
+        for index in 1..=4 {
+            let selector = CaseSelector::new().shard_spec(&format!("{}/4", index)).unwrap();
+            counts.push(cases.iter().filter(|c| selector.matches(c)).count());
+        }
+        assert_eq!(counts.iter().sum::<usize>(), 100);
+        assert!(counts.iter().all(|&n| n > 0));
+
+        assert!(CaseSelector::new().shard(0, 4).is_err());
+        assert!(CaseSelector::new().shard_spec("5/4").is_err());
+        assert!(CaseSelector::new().shard_spec("two").is_err());
+    }
+}
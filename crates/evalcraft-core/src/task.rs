@@ -1,13 +1,73 @@
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::types::TestCase;
+
+/// What the runner knows about the call it's making to a task.
+#[derive(Debug, Clone)]
+pub struct TaskContext {
+	pub case_id: Option<String>,
+	pub tags: Vec<String>,
+	/// The case's free-form metadata (e.g. a fixture DB name).
+	pub metadata: Value,
+	/// 0-based trial index (see `EvalBuilder::trials`).
+	pub trial: usize,
+	/// 1-based attempt number; above 1 when the call is a retry.
+	pub attempt: u32,
+	/// Cancelled when the eval is (see `EvalBuilder::cancel_token`).
+	pub cancel: CancellationToken,
+	/// When the runner gives up on this call (case timeout or run deadline).
+	pub deadline: Option<Instant>,
+}
+
+impl Default for TaskContext {
+	fn default() -> Self {
+		Self {
+			case_id: None,
+			tags: Vec::new(),
+			metadata: Value::Null,
+			trial: 0,
+			attempt: 1,
+			cancel: CancellationToken::new(),
+			deadline: None,
+		}
+	}
+}
+
+impl TaskContext {
+	/// First attempt of the first trial of `case`.
+	pub fn for_case(case: &TestCase) -> Self {
+		Self {
+			case_id: case.id.clone(),
+			tags: case.tags.clone(),
+			metadata: case.metadata.clone(),
+			..Self::default()
+		}
+	}
+
+	/// Time left until `deadline`, if there is one.
+	pub fn remaining(&self) -> Option<Duration> {
+		self.deadline.map(|d| d.saturating_duration_since(Instant::now()))
+	}
+}
 
 #[async_trait]
 pub trait Task: Send + Sync {
 	async fn run(&self, input: &Value) -> Result<Value>;
+
+	/// Run with the case's id, tags, metadata, trial/attempt numbers, cancellation
+	/// token and deadline. The runner calls this; the default ignores the context
+	/// and calls `run`.
+	async fn run_with_context(&self, input: &Value, _ctx: &TaskContext) -> Result<Value> {
+		self.run(input).await
+	}
 }
 
 /// Wrap an async closure as a `Task`.
@@ -38,4 +98,31 @@ where
 	Arc::new(ClosureTask { f })
 }
 
+/// Wrap an async closure that also takes the `TaskContext` as a `Task`. Called
+/// without a runner (plain `Task::run`), it gets `TaskContext::default()`.
+pub fn from_async_fn_ctx<F, Fut>(f: F) -> Arc<dyn Task>
+where
+	F: Send + Sync + 'static + Fn(&Value, &TaskContext) -> Fut,
+	Fut: Future<Output = Result<Value>> + Send + 'static,
+{
+	struct ContextClosureTask<F> {
+		f: F,
+	}
 
+	#[async_trait]
+	impl<F, Fut> Task for ContextClosureTask<F>
+	where
+		F: Send + Sync + 'static + Fn(&Value, &TaskContext) -> Fut,
+		Fut: Future<Output = Result<Value>> + Send + 'static,
+	{
+		async fn run(&self, input: &Value) -> Result<Value> {
+			self.run_with_context(input, &TaskContext::default()).await
+		}
+
+		async fn run_with_context(&self, input: &Value, ctx: &TaskContext) -> Result<Value> {
+			(self.f)(input, ctx).await
+		}
+	}
+
+	Arc::new(ContextClosureTask { f })
+}
@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::task::{Task, TaskContext};
+use crate::trace::{report_trace, scope_traces, Trace};
+use crate::types::stable_hash;
+
+/// Selects the cache mode: `record`, `replay` or `off` (the default).
+pub const CACHE_ENV: &str = "EVALCRAFT_CACHE";
+
+const DEFAULT_DIR: &str = ".evalcraft/cache";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// Always call the wrapped task.
+    #[default]
+    Off,
+    /// Call the wrapped task and save each successful output with its traces.
+    Record,
+    /// Serve outputs and traces from the cassette only; a missing entry is an error.
+    Replay,
+}
+
+impl CacheMode {
+    /// Read `EVALCRAFT_CACHE`; unset or empty means `Off`.
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(CACHE_ENV).unwrap_or_default().trim() {
+            "" | "off" => Ok(CacheMode::Off),
+            "record" => Ok(CacheMode::Record),
+            "replay" => Ok(CacheMode::Replay),
+            other => bail!("Invalid {}: {:?} (expected record, replay or off)", CACHE_ENV, other),
+        }
+    }
+}
+
+/// One recorded case on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    input: Value,
+    output: Value,
+    #[serde(default)]
+    traces: Vec<Trace>,
+}
+
+/// Record/replay wrapper ("cassette") around another task, for deterministic,
+/// offline re-runs, e.g. while iterating on scorers.
+///
+/// Entries are JSON files named by a hash of `identity` and the case input.
+/// Change `identity` whenever the wrapped task would answer differently (model,
+/// prompt, endpoint) so stale recordings aren't replayed.
+pub struct CachedTask {
+    inner: Arc<dyn Task>,
+    identity: String,
+    dir: PathBuf,
+    mode: CacheMode,
+}
+
+impl CachedTask {
+    /// Wrap `inner`, storing entries under `.evalcraft/cache` in the mode given by
+    /// `EVALCRAFT_CACHE`.
+    pub fn new(inner: Arc<dyn Task>, identity: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            inner,
+            identity: identity.into(),
+            dir: PathBuf::from(DEFAULT_DIR),
+            mode: CacheMode::from_env()?,
+        })
+    }
+
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    /// Override the mode from the environment.
+    pub fn mode(mut self, mode: CacheMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn key(&self, input: &Value) -> String {
+        let content = format!("{}\n{}", self.identity, input);
+        format!("{:016x}", stable_hash(content.as_bytes()))
+    }
+
+    fn path(&self, input: &Value) -> PathBuf {
+        self.dir.join(format!("{}.json", self.key(input)))
+    }
+
+    async fn record(&self, input: &Value, ctx: &TaskContext) -> Result<Value> {
+        let (output, traces) = scope_traces(self.inner.run_with_context(input, ctx)).await;
+        for trace in &traces {
+            report_trace(trace.clone());
+        }
+        let output = output?;
+
+        let entry = Entry { input: input.clone(), output: output.clone(), traces };
+        let path = self.path(input);
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .with_context(|| format!("Failed to create cache dir {:?}", self.dir))?;
+        tokio::fs::write(&path, serde_json::to_vec_pretty(&entry)?)
+            .await
+            .with_context(|| format!("Failed to write cache entry {:?}", path))?;
+        Ok(output)
+    }
+
+    async fn replay(&self, input: &Value) -> Result<Value> {
+        let path = self.path(input);
+        let content = match tokio::fs::read(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(anyhow!(
+                    "Cache miss in replay mode for input {} ({:?} not found); record it with {}=record",
+                    input,
+                    path,
+                    CACHE_ENV
+                ))
+            }
+            Err(e) => return Err(e).with_context(|| format!("Failed to read cache entry {:?}", path)),
+        };
+        let entry: Entry =
+            serde_json::from_slice(&content).with_context(|| format!("Invalid cache entry {:?}", path))?;
+        for trace in entry.traces {
+            report_trace(trace);
+        }
+        Ok(entry.output)
+    }
+}
+
+#[async_trait]
+impl Task for CachedTask {
+    async fn run(&self, input: &Value) -> Result<Value> {
+        self.run_with_context(input, &TaskContext::default()).await
+    }
+
+    async fn run_with_context(&self, input: &Value, ctx: &TaskContext) -> Result<Value> {
+        match self.mode {
+            CacheMode::Off => self.inner.run_with_context(input, ctx).await,
+            CacheMode::Record => self.record(input, ctx).await,
+            CacheMode::Replay => self.replay(input).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::from_async_fn;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = std::env::temp_dir().join(format!("evalcraft-cache-{}", std::process::id()));
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let inner = from_async_fn(move |input| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let input = input.clone();
+            async move {
+                report_trace(Trace::start_now().model("m").finish(input.clone(), json!("traced"), None));
+                Ok(json!({ "echo": input }))
+            }
+        });
+        let cached = |mode| CachedTask::new(inner.clone(), "echo-v1").unwrap().dir(&dir).mode(mode);
+
+        let (output, traces) = scope_traces(cached(CacheMode::Record).run(&json!("q"))).await;
+        assert_eq!(output.unwrap(), json!({ "echo": "q" }));
+        assert_eq!(traces.len(), 1);
+
+        let (output, traces) = scope_traces(cached(CacheMode::Replay).run(&json!("q"))).await;
+        assert_eq!(output.unwrap(), json!({ "echo": "q" }));
+        assert_eq!(traces[0].model.as_deref(), Some("m"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let miss = cached(CacheMode::Replay).run(&json!("other")).await.unwrap_err();
+        assert!(miss.to_string().contains("Cache miss"));
+        // A different identity doesn't see the recording.
+        let renamed = CachedTask::new(inner, "echo-v2").unwrap().dir(&dir).mode(CacheMode::Replay);
+        assert!(renamed.run(&json!("q")).await.is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
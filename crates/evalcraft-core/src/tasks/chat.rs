@@ -0,0 +1,214 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::task::Task;
+use crate::trace::{report_trace, TokenUsage, Trace};
+
+/// Calls an OpenAI-compatible `/chat/completions` endpoint for each case.
+///
+/// A string input becomes the user message; an object with a `messages` array is
+/// sent as-is (after the system prompt); any other input is sent as JSON text.
+/// The output is the first choice's message content (parsed as JSON in JSON mode),
+/// and every call is reported as a `Trace` with token usage.
+pub struct ChatCompletionTask {
+    client: Client,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    system_prompt: Option<String>,
+    temperature: Option<f64>,
+    max_tokens: Option<u32>,
+    response_format: Option<Value>,
+}
+
+impl ChatCompletionTask {
+    /// `base_url` is the API root, e.g. `http://localhost:8080` or `https://api.openai.com/v1`.
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.into(),
+            model: model.into(),
+            api_key: None,
+            system_prompt: None,
+            temperature: None,
+            max_tokens: None,
+            response_format: None,
+        }
+    }
+
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Raw `response_format` value, e.g. `{"type": "json_schema", "json_schema": {...}}`.
+    pub fn response_format(mut self, format: Value) -> Self {
+        self.response_format = Some(format);
+        self
+    }
+
+    /// Shorthand for `response_format({"type": "json_object"})`.
+    pub fn json_mode(self) -> Self {
+        self.response_format(json!({ "type": "json_object" }))
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/chat/completions", self.base_url.trim_end_matches('/'))
+    }
+
+    fn request_body(&self, input: &Value) -> Value {
+        let mut messages = Vec::new();
+        if let Some(prompt) = &self.system_prompt {
+            messages.push(json!({ "role": "system", "content": prompt }));
+        }
+        match input {
+            Value::String(s) => messages.push(json!({ "role": "user", "content": s })),
+            Value::Object(obj) if obj.get("messages").is_some_and(Value::is_array) => {
+                messages.extend(obj["messages"].as_array().into_iter().flatten().cloned());
+            }
+            other => messages.push(json!({ "role": "user", "content": other.to_string() })),
+        }
+
+        let mut body = json!({ "model": self.model, "messages": messages });
+        if let Some(t) = self.temperature {
+            body["temperature"] = json!(t);
+        }
+        if let Some(n) = self.max_tokens {
+            body["max_tokens"] = json!(n);
+        }
+        if let Some(format) = &self.response_format {
+            body["response_format"] = format.clone();
+        }
+        body
+    }
+
+    async fn call(&self, body: &Value) -> Result<Value> {
+        let mut request = self.client.post(self.endpoint()).json(body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?.error_for_status()?;
+        Ok(response.json().await?)
+    }
+}
+
+#[async_trait]
+impl Task for ChatCompletionTask {
+    async fn run(&self, input: &Value) -> Result<Value> {
+        let body = self.request_body(input);
+        let trace = Trace::start_now().model(self.model.clone()).metadata(json!({
+            "provider": "openai_compatible",
+            "base_url": self.base_url,
+            "temperature": self.temperature,
+            "max_tokens": self.max_tokens,
+        }));
+
+        let response = match self.call(&body).await {
+            Ok(response) => response,
+            Err(e) => {
+                report_trace(trace.finish_with_error(body, e.to_string()));
+                return Err(e);
+            }
+        };
+
+        let usage = response.get("usage").map(|u| {
+            let field = |name: &str| u.get(name).and_then(Value::as_u64).unwrap_or(0) as u32;
+            TokenUsage {
+                input_tokens: field("prompt_tokens"),
+                output_tokens: field("completion_tokens"),
+                total_tokens: field("total_tokens"),
+            }
+        });
+        let content = response["choices"][0]["message"]["content"].as_str().map(str::to_string);
+        report_trace(trace.finish(body, response.clone(), usage));
+
+        let content = content.ok_or_else(|| anyhow!("No message content in response: {}", response))?;
+        if self.response_format.is_some() {
+            Ok(serde_json::from_str(&content).unwrap_or(Value::String(content)))
+        } else {
+            Ok(Value::String(content))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::scope_traces;
+    use crate::test_util::serve_once;
+
+    #[tokio::test]
+    async fn test_chat_completion_with_usage_trace() {
+        let (url, request) = serve_once(
+            200,
+            json!({
+                "choices": [{"message": {"role": "assistant", "content": "Paris"}}],
+                "usage": {"prompt_tokens": 12, "completion_tokens": 1, "total_tokens": 13}
+            }),
+        )
+        .await;
+
+        let task = ChatCompletionTask::new(url, "test-model")
+            .system_prompt("Answer in one word.")
+            .temperature(0.0)
+            .max_tokens(16);
+        let (output, traces) = scope_traces(task.run(&json!("Capital of France?"))).await;
+
+        assert_eq!(output.unwrap(), json!("Paris"));
+        let body = request.await.unwrap();
+        assert_eq!(body["model"], json!("test-model"));
+        assert_eq!(body["messages"][0]["role"], json!("system"));
+        assert_eq!(body["messages"][1]["content"], json!("Capital of France?"));
+        assert_eq!(body["max_tokens"], json!(16));
+
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].model.as_deref(), Some("test-model"));
+        assert_eq!(traces[0].usage.as_ref().unwrap().total_tokens, 13);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_json_mode() {
+        let (url, request) = serve_once(
+            200,
+            json!({"choices": [{"message": {"content": "{\"answer\": 42}"}}]}),
+        )
+        .await;
+
+        let task = ChatCompletionTask::new(url, "m").json_mode();
+        let (output, _) = scope_traces(task.run(&json!({"messages": [{"role": "user", "content": "hi"}]}))).await;
+
+        assert_eq!(output.unwrap(), json!({"answer": 42}));
+        let body = request.await.unwrap();
+        assert_eq!(body["response_format"], json!({"type": "json_object"}));
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_error_trace() {
+        let (url, _request) = serve_once(500, json!({"error": "boom"})).await;
+
+        let task = ChatCompletionTask::new(url, "m");
+        let (output, traces) = scope_traces(task.run(&json!("hi"))).await;
+
+        assert!(output.is_err());
+        assert_eq!(traces.len(), 1);
+        assert!(traces[0].error.is_some());
+    }
+}
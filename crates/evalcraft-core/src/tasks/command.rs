@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+
+use crate::task::Task;
+use crate::trace::{report_trace, TokenUsage, Trace};
+
+/// Runs an external program for each case, speaking JSON over stdin/stdout.
+///
+/// By default a new process is spawned per case: the case input is written to
+/// stdin as JSON, stdin is closed, and the whole of stdout is parsed as the
+/// output (falling back to a string if it isn't JSON).
+///
+/// With `persistent(true)` a single process is kept alive and handles cases one
+/// at a time: each input is written as one JSON line and one line is read back
+/// from stdout as the output.
+///
+/// Traces are JSON objects (`{"model": ..., "input": ..., "output": ...,
+/// "usage": {...}}`) turned into `Trace`s via `report_trace`. Per process, they
+/// are read from stderr. In persistent mode stderr is only forwarded as logs;
+/// the child instead writes each trace to stdout as a `{"trace": {...}}` line
+/// before the case's output line, so traces always land on the right case.
+/// There is no fd 3 channel.
+pub struct CommandTask {
+    program: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    timeout: Option<Duration>,
+    persistent: bool,
+    worker: Mutex<Option<Worker>>,
+}
+
+impl CommandTask {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            timeout: None,
+            persistent: false,
+            worker: Mutex::new(None),
+        }
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Fail a case (and kill the process) if it takes longer than `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Keep one process alive across cases instead of spawning per case.
+    pub fn persistent(mut self, persistent: bool) -> Self {
+        self.persistent = persistent;
+        self
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args)
+            .envs(&self.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        cmd
+    }
+
+    async fn run_once(&self, input: &Value) -> Result<Value> {
+        let mut child = self
+            .command()
+            .spawn()
+            .with_context(|| format!("Failed to spawn {}", self.program))?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("stdin not captured"))?;
+        let payload = serde_json::to_vec(input)?;
+        let write = async move {
+            match stdin.write_all(&payload).await {
+                // The child may legitimately exit without reading its input.
+                Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e),
+                _ => Ok(()),
+            }
+        };
+
+        let (write_result, output) = tokio::join!(write, child.wait_with_output());
+        write_result?;
+        let output = output?;
+
+        let mut logs = Vec::new();
+        for line in String::from_utf8_lossy(&output.stderr).lines() {
+            match parse_trace_line(line) {
+                Some(trace) => report_trace(trace),
+                None => logs.push(line.to_string()),
+            }
+        }
+
+        if !output.status.success() {
+            bail!(
+                "{} exited with {}: {}",
+                self.program,
+                output.status,
+                logs.join("\n").trim()
+            );
+        }
+
+        Ok(parse_output(String::from_utf8_lossy(&output.stdout).trim()))
+    }
+
+    async fn run_persistent(&self, input: &Value) -> Result<Value> {
+        let mut slot = self.worker.lock().await;
+        let mut worker = match slot.take() {
+            Some(worker) => worker,
+            None => self.spawn_worker()?,
+        };
+
+        // The worker only goes back after a complete response. On an error, or if
+        // this future is dropped mid-call (timeout, deadline, cancel), it is dropped
+        // (and killed) so the next case doesn't read this case's output.
+        let output = worker.call(input).await?;
+        *slot = Some(worker);
+        Ok(output)
+    }
+
+    fn spawn_worker(&self) -> Result<Worker> {
+        let mut child = self
+            .command()
+            .spawn()
+            .with_context(|| format!("Failed to spawn {}", self.program))?;
+
+        let stdin = child.stdin.take().ok_or_else(|| anyhow!("stdin not captured"))?;
+        let stdout = child.stdout.take().ok_or_else(|| anyhow!("stdout not captured"))?;
+        let stderr = child.stderr.take().ok_or_else(|| anyhow!("stderr not captured"))?;
+
+        let program = self.program.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                eprintln!("[{}] {}", program, line);
+            }
+        });
+
+        Ok(Worker {
+            _child: child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+        })
+    }
+}
+
+#[async_trait]
+impl Task for CommandTask {
+    async fn run(&self, input: &Value) -> Result<Value> {
+        let run = async {
+            if self.persistent {
+                self.run_persistent(input).await
+            } else {
+                self.run_once(input).await
+            }
+        };
+
+        match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, run).await {
+                Ok(result) => result,
+                Err(_) => bail!("{} timed out after {:?}", self.program, timeout),
+            },
+            None => run.await,
+        }
+    }
+}
+
+struct Worker {
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl Worker {
+    async fn call(&mut self, input: &Value) -> Result<Value> {
+        let mut line = serde_json::to_string(input)?;
+        line.push('\n');
+        self.stdin.write_all(line.as_bytes()).await?;
+        self.stdin.flush().await?;
+
+        loop {
+            let response = self
+                .stdout
+                .next_line()
+                .await?
+                .ok_or_else(|| anyhow!("process exited before responding"))?;
+
+            match parse_trace_envelope(&response) {
+                Some(trace) => report_trace(trace),
+                None => return Ok(parse_output(response.trim())),
+            }
+        }
+    }
+}
+
+/// A `{"trace": {...}}` line written to stdout by a persistent process.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TraceEnvelope {
+    trace: TraceLine,
+}
+
+/// Trace shape accepted on the side channel; timestamps are filled in on receipt.
+#[derive(Deserialize)]
+struct TraceLine {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    model: Option<String>,
+    input: Value,
+    #[serde(default)]
+    output: Value,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
+    #[serde(default)]
+    duration_ms: Option<u64>,
+    #[serde(default)]
+    metadata: Option<Value>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+fn parse_trace_line(line: &str) -> Option<Trace> {
+    serde_json::from_str(line.trim()).ok().map(into_trace)
+}
+
+fn parse_trace_envelope(line: &str) -> Option<Trace> {
+    serde_json::from_str::<TraceEnvelope>(line.trim())
+        .ok()
+        .map(|envelope| into_trace(envelope.trace))
+}
+
+fn into_trace(line: TraceLine) -> Trace {
+    let end = SystemTime::now();
+    let start = line
+        .duration_ms
+        .and_then(|ms| end.checked_sub(Duration::from_millis(ms)))
+        .unwrap_or(end);
+    Trace {
+        id: line.id,
+        start,
+        end,
+        duration_ms: line.duration_ms,
+        model: line.model,
+        input: line.input,
+        output: line.output,
+        usage: line.usage,
+        metadata: line.metadata,
+        error: line.error,
+    }
+}
+
+fn parse_output(stdout: &str) -> Value {
+    serde_json::from_str(stdout).unwrap_or_else(|_| Value::String(stdout.to_string()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::trace::scope_traces;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_command_echo() {
+        let task = CommandTask::new("cat");
+        let output = task.run(&json!({"question": "hi"})).await.unwrap();
+        assert_eq!(output, json!({"question": "hi"}));
+    }
+
+    #[tokio::test]
+    async fn test_command_traces_on_stderr() {
+        let script = r#"cat >/dev/null; echo '{"model":"m","input":"q","output":"a","usage":{"input_tokens":1,"output_tokens":2,"total_tokens":3}}' >&2; echo 'not a trace' >&2; echo '"done"'"#;
+        let task = CommandTask::new("sh").args(["-c", script]);
+        let (output, traces) = scope_traces(task.run(&json!("q"))).await;
+        assert_eq!(output.unwrap(), json!("done"));
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].model.as_deref(), Some("m"));
+        assert_eq!(traces[0].usage.as_ref().unwrap().total_tokens, 3);
+    }
+
+    #[tokio::test]
+    async fn test_command_failure() {
+        let task = CommandTask::new("sh").args(["-c", "echo boom >&2; exit 3"]);
+        let err = task.run(&json!("q")).await.unwrap_err();
+        assert!(err.to_string().contains("boom"));
+    }
+
+    #[tokio::test]
+    async fn test_command_persistent() {
+        let task = CommandTask::new("sh")
+            .args(["-c", "while read line; do echo \"$line\"; done"])
+            .persistent(true);
+        assert_eq!(task.run(&json!(1)).await.unwrap(), json!(1));
+        assert_eq!(task.run(&json!({"a": 2})).await.unwrap(), json!({"a": 2}));
+    }
+
+    #[tokio::test]
+    async fn test_command_persistent_traces() {
+        let script = r#"while read line; do
+            echo "{\"trace\":{\"model\":\"m\",\"input\":$line,\"output\":\"a\"}}"
+            echo '{"model":"stderr","input":"ignored"}' >&2
+            echo "$line"
+        done"#;
+        let task = CommandTask::new("sh").args(["-c", script]).persistent(true);
+        for i in 0..3 {
+            let (output, traces) = scope_traces(task.run(&json!(i))).await;
+            assert_eq!(output.unwrap(), json!(i));
+            assert_eq!(traces.len(), 1);
+            assert_eq!(traces[0].model.as_deref(), Some("m"));
+            assert_eq!(traces[0].input, json!(i));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_command_persistent_dropped_call() {
+        let script = r#"while read line; do
+            if [ "$line" = '"slow"' ]; then sleep 1; fi
+            echo "$line"
+        done"#;
+        let task = CommandTask::new("sh").args(["-c", script]).persistent(true);
+        // Dropped mid-call, as the runner does on case_timeout, run_deadline or cancel.
+        let slow = tokio::time::timeout(Duration::from_millis(100), task.run(&json!("slow"))).await;
+        assert!(slow.is_err());
+        assert_eq!(task.run(&json!(2)).await.unwrap(), json!(2));
+    }
+
+    #[tokio::test]
+    async fn test_command_timeout() {
+        let task = CommandTask::new("sleep")
+            .args(["5"])
+            .timeout(Duration::from_millis(100));
+        let err = task.run(&json!(null)).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+}
@@ -0,0 +1,158 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::{Client, Method};
+use serde_json::Value;
+
+use crate::task::Task;
+use crate::template;
+
+/// Sends each case input to an HTTP endpoint and uses the response as output.
+///
+/// Without a body template the request body is `{"input": <case input>}`. JSON
+/// responses are parsed into a `Value`; any other body is returned as a JSON string.
+pub struct HttpTask {
+    client: Client,
+    url: String,
+    method: Method,
+    body: Option<Value>,
+    headers: Vec<(String, String)>,
+    query: Vec<(String, String)>,
+    output: Option<String>,
+}
+
+impl HttpTask {
+    /// Creates a task that POSTs to `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.into(),
+            method: Method::POST,
+            body: None,
+            headers: Vec::new(),
+            query: Vec::new(),
+            output: None,
+        }
+    }
+
+    /// Creates a task with an explicit HTTP method (e.g. "GET", "PUT").
+    pub fn with_method(url: impl Into<String>, method: &str) -> Result<Self> {
+        let method = Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| anyhow!("Invalid HTTP method: {}", method))?;
+        Ok(Self {
+            method,
+            ..Self::new(url)
+        })
+    }
+
+    /// Request body template; `{{input}}` / `{{input.field}}` placeholders are filled per case.
+    pub fn body(mut self, template: Value) -> Self {
+        self.body = Some(template);
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Query parameter; the value may contain `{{input...}}` placeholders.
+    pub fn query(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((name.into(), value.into()));
+        self
+    }
+
+    /// JSONPath-style selector (e.g. `$.choices[0].message.content`) applied to the response.
+    pub fn output(mut self, selector: impl Into<String>) -> Self {
+        self.output = Some(selector.into());
+        self
+    }
+}
+
+#[async_trait]
+impl Task for HttpTask {
+    async fn run(&self, input: &Value) -> Result<Value> {
+        let context = serde_json::json!({ "input": input });
+
+        let mut request = self.client.request(self.method.clone(), &self.url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        if !self.query.is_empty() {
+            let query = self
+                .query
+                .iter()
+                .map(|(name, value)| {
+                    let value = match template::render(&Value::String(value.clone()), &context)? {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    Ok((name.clone(), value))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            request = request.query(&query);
+        }
+        if self.method != Method::GET && self.method != Method::HEAD {
+            let body = match &self.body {
+                Some(body) => template::render(body, &context)?,
+                None => context,
+            };
+            request = request.json(&body);
+        }
+
+        let response = request.send().await?.error_for_status()?;
+        let body = response.text().await?;
+        let value = serde_json::from_str(&body).unwrap_or(Value::String(body));
+
+        match &self.output {
+            Some(selector) => template::select(&value, selector)
+                .cloned()
+                .ok_or_else(|| anyhow!("Output selector '{}' not found in response: {}", selector, value)),
+            None => Ok(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::serve;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_run_renders_request_and_selects_output() {
+        let (url, request) = serve(200, json!({ "choices": [{ "message": { "content": "Paris" } }] })).await;
+        let task = HttpTask::new(format!("{}/chat", url))
+            .header("Authorization", "Bearer secret")
+            .query("lang", "{{input.lang}}")
+            .body(json!({ "messages": [{ "role": "user", "content": "Q: {{input.question}}" }], "raw": "{{input}}" }))
+            .output("$.choices[0].message.content");
+        let input = json!({ "question": "Capital of France?", "lang": "en" });
+
+        assert_eq!(task.run(&input).await.unwrap(), json!("Paris"));
+        let request = request.await.unwrap();
+        assert!(request.head.starts_with("POST "));
+        assert_eq!(request.target(), "/chat?lang=en");
+        assert_eq!(request.header("authorization"), Some("Bearer secret"));
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["messages"][0]["content"], json!("Q: Capital of France?"));
+        assert_eq!(body["raw"], input);
+    }
+
+    #[tokio::test]
+    async fn test_run_default_body_and_missing_selector() {
+        let (url, request) = serve(200, json!({ "answer": 42 })).await;
+        let task = HttpTask::new(url).output("result");
+
+        let err = task.run(&json!("q")).await.unwrap_err();
+        assert!(err.to_string().contains("Output selector 'result' not found"));
+        let body: Value = serde_json::from_slice(&request.await.unwrap().body).unwrap();
+        assert_eq!(body, json!({ "input": "q" }));
+    }
+
+    #[tokio::test]
+    async fn test_run_error_status() {
+        let (url, _request) = serve(503, json!({ "error": "overloaded" })).await;
+        let err = HttpTask::new(url).run(&json!("q")).await.unwrap_err();
+        assert!(err.downcast_ref::<reqwest::Error>().and_then(|e| e.status()).is_some_and(|s| s.as_u16() == 503));
+    }
+}
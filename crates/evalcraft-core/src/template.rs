@@ -0,0 +1,147 @@
+//! Small helpers for config-driven tasks: `{{path}}` templates, `${ENV}`
+//! interpolation and JSONPath-style selectors (`$.choices[0].message.content`).
+
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Result};
+use regex::{Captures, Regex};
+use serde_json::Value;
+
+fn placeholder_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\{\{\s*([^{}]+?)\s*\}\}").unwrap())
+}
+
+fn env_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?::-([^}]*))?\}").unwrap())
+}
+
+/// Select a nested value using a dotted path.
+///
+/// Accepts `a.b.c`, `a.items[0].name`, `a.items.0.name` and an optional leading `$`.
+/// An empty path (or `$`) selects the value itself.
+pub fn select<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let normalized = path.replace('[', ".").replace(']', "");
+
+    let mut current = value;
+    for segment in normalized.split('.').filter(|s| !s.is_empty()) {
+        current = match current {
+            Value::Object(map) => map.get(segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Render `{{path}}` placeholders in every string of `template`, looking paths up in `context`.
+///
+/// A string that consists of a single placeholder is replaced by the selected value
+/// itself (so `"{{input}}"` can inject an object); otherwise the value is interpolated
+/// as text.
+pub fn render(template: &Value, context: &Value) -> Result<Value> {
+    match template {
+        Value::String(s) => render_string(s, context),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| render(item, context))
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), render(v, context)?)))
+            .collect::<Result<serde_json::Map<_, _>>>()
+            .map(Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
+fn render_string(s: &str, context: &Value) -> Result<Value> {
+    let re = placeholder_re();
+
+    if let Some(caps) = re.captures(s.trim()) {
+        if caps.get(0).map(|m| m.as_str()) == Some(s.trim()) {
+            return lookup(context, &caps[1]).cloned();
+        }
+    }
+
+    let mut missing = None;
+    let rendered = re.replace_all(s, |caps: &Captures| match lookup(context, &caps[1]) {
+        Ok(Value::String(v)) => v.clone(),
+        Ok(v) => v.to_string(),
+        Err(e) => {
+            missing.get_or_insert(e);
+            String::new()
+        }
+    });
+    match missing {
+        Some(e) => Err(e),
+        None => Ok(Value::String(rendered.into_owned())),
+    }
+}
+
+fn lookup<'a>(context: &'a Value, path: &str) -> Result<&'a Value> {
+    select(context, path).ok_or_else(|| anyhow!("Template variable '{}' not found", path))
+}
+
+/// Replace `${VAR}` with the value of the environment variable `VAR`, and
+/// `${VAR:-default}` with `default` when `VAR` is unset.
+pub fn interpolate_env(s: &str) -> Result<String> {
+    let mut missing = None;
+    let rendered = env_re().replace_all(s, |caps: &Captures| {
+        std::env::var(&caps[1]).unwrap_or_else(|_| match caps.get(2) {
+            Some(default) => default.as_str().to_string(),
+            None => {
+                missing.get_or_insert_with(|| caps[1].to_string());
+                String::new()
+            }
+        })
+    });
+    match missing {
+        Some(var) => Err(anyhow!("Environment variable '{}' is not set", var)),
+        None => Ok(rendered.into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select() {
+        let v = json!({"choices": [{"message": {"content": "Paris"}}]});
+        assert_eq!(select(&v, "$.choices[0].message.content"), Some(&json!("Paris")));
+        assert_eq!(select(&v, "choices.0.message.content"), Some(&json!("Paris")));
+        assert_eq!(select(&v, "$"), Some(&v));
+        assert_eq!(select(&v, "choices[1]"), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let context = json!({"input": {"question": "Capital of France?", "n": 2}});
+        let template = json!({
+            "messages": [{"role": "user", "content": "Q: {{input.question}} ({{ input.n }})"}],
+            "raw": "{{input}}"
+        });
+        let rendered = render(&template, &context).unwrap();
+        assert_eq!(rendered["messages"][0]["content"], json!("Q: Capital of France? (2)"));
+        assert_eq!(rendered["raw"], context["input"]);
+        assert!(render(&json!("{{input.missing}}"), &context).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_env() {
+        std::env::set_var("EVALCRAFT_TEMPLATE_TEST_KEY", "secret");
+        assert_eq!(
+            interpolate_env("Bearer ${EVALCRAFT_TEMPLATE_TEST_KEY}").unwrap(),
+            "Bearer secret"
+        );
+        assert!(interpolate_env("${EVALCRAFT_TEMPLATE_TEST_UNSET}").is_err());
+        assert_eq!(interpolate_env("${EVALCRAFT_TEMPLATE_TEST_UNSET:-none}").unwrap(), "none");
+        assert_eq!(interpolate_env("${EVALCRAFT_TEMPLATE_TEST_KEY:-none}").unwrap(), "secret");
+    }
+}
@@ -0,0 +1,74 @@
+//! A minimal one-shot HTTP server for tests of HTTP-based tasks.
+
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// The request received by `serve`.
+pub struct Request {
+    /// Request line and headers, e.g. `POST /echo?q=1 HTTP/1.1\r\nhost: ...`.
+    pub head: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The value of header `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+
+    /// Path and query of the request line.
+    pub fn target(&self) -> &str {
+        self.head.split(' ').nth(1).unwrap_or_default()
+    }
+}
+
+/// Answer a single HTTP request with `status` and a JSON `response`, and hand
+/// back the request.
+pub async fn serve(status: u16, response: Value) -> (String, JoinHandle<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let body_start = loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+        let head = String::from_utf8_lossy(&buf[..body_start - 4]).into_owned();
+        let content_length: usize = head
+            .lines()
+            .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+            .unwrap_or(0);
+        while buf.len() < body_start + content_length {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        let payload = response.to_string();
+        let reply = format!(
+            "HTTP/1.1 {} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            payload.len(),
+            payload
+        );
+        socket.write_all(reply.as_bytes()).await.unwrap();
+        Request { head, body: buf[body_start..].to_vec() }
+    });
+    (url, handle)
+}
+
+/// Like `serve`, handing back the request body parsed as JSON.
+pub async fn serve_once(status: u16, response: Value) -> (String, JoinHandle<Value>) {
+    let (url, request) = serve(status, response).await;
+    let body = tokio::spawn(async move { serde_json::from_slice(&request.await.unwrap().body).unwrap() });
+    (url, body)
+}
@@ -1,6 +1,18 @@
-use crate::types::EvalResult;
+use crate::types::{EvalResult, RunStatus};
 use anyhow::Result;
 
+/// A failed run only scored the cases read before the error, so it can't pass.
+fn ensure_not_failed(result: &EvalResult) -> Result<()> {
+    if result.status == RunStatus::Failed {
+        anyhow::bail!(
+            "Evaluation failed: {}\n{}",
+            result.error.as_deref().unwrap_or("unknown error"),
+            result.summary_table()
+        );
+    }
+    Ok(())
+}
+
 /// Helper to assert evaluation pass rate meets a threshold.
 ///
 /// Use this in your `#[tokio::test]` functions.
@@ -24,6 +36,7 @@ use anyhow::Result;
 /// }
 /// ```
 pub fn assert_eval_pass_rate(result: &EvalResult, min_pass_rate: f64) -> Result<()> {
+    ensure_not_failed(result)?;
     if result.summary.pass_rate < min_pass_rate {
         anyhow::bail!(
             "Evaluation failed: pass rate {:.1}% is below threshold {:.1}%\n{}",
@@ -35,8 +48,9 @@ pub fn assert_eval_pass_rate(result: &EvalResult, min_pass_rate: f64) -> Result<
     Ok(())
 }
 
-/// Helper to assert average score meets a threshold.
+/// Helper to assert the average of the cases' aggregate scores meets a threshold.
 pub fn assert_eval_avg_score(result: &EvalResult, min_avg_score: f64) -> Result<()> {
+    ensure_not_failed(result)?;
     if result.summary.avg_score < min_avg_score {
         anyhow::bail!(
             "Evaluation failed: avg score {:.3} is below threshold {:.3}\n{}",
@@ -48,8 +62,9 @@ pub fn assert_eval_avg_score(result: &EvalResult, min_avg_score: f64) -> Result<
     Ok(())
 }
 
-/// Helper to assert all cases passed.
+/// Helper to assert all cases passed under the eval's `PassPolicy`.
 pub fn assert_eval_all_passed(result: &EvalResult) -> Result<()> {
+    ensure_not_failed(result)?;
     if result.summary.passed != result.summary.total {
         anyhow::bail!(
             "Evaluation failed: {}/{} cases passed\n{}",
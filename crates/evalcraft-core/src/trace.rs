@@ -67,7 +67,7 @@ mod tests {
     #[tokio::test]
     async fn test_report_and_get_traces() {
         // We need to run this inside the trace scope
-        let (_, traces) = scope_traces(async {
+        let (_, _traces) = scope_traces(async {
             clear_traces();
             
             let trace1 = Trace::start_now()
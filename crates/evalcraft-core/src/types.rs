@@ -1 +1 @@
-pub use evalcraft_types::{TestCase, Score, CaseResult, EvalSummary, EvalResult};
+pub use evalcraft_types::{TestCase, Score, CaseResult, CaseScore, ConcurrencySample, ErrorKind, EvalSummary, EvalResult, RunStatus, SummaryAccumulator, Trial, TrialStats, TrialSummary, stable_hash};
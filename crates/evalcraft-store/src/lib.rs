@@ -1,12 +1,13 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, OpenFlags};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 // Use shared types
-use evalcraft_types::EvalResult;
+use evalcraft_types::{CaseResult, CaseScore, EvalResult, EvalSummary, RunStatus, Score, TestCase};
 
 #[derive(Debug)]
 pub struct Store {
@@ -57,6 +58,7 @@ impl Store {
                 run_id INTEGER NOT NULL,
                 name TEXT NOT NULL,
                 summary TEXT,
+                status TEXT,
                 FOREIGN KEY(run_id) REFERENCES runs(id)
             )",
             [],
@@ -66,15 +68,35 @@ impl Store {
             "CREATE TABLE IF NOT EXISTS results (
                 id INTEGER PRIMARY KEY,
                 eval_id INTEGER NOT NULL,
+                case_index INTEGER,
                 case_id TEXT,
                 input TEXT NOT NULL,
                 output TEXT NOT NULL,
                 expected TEXT NOT NULL,
                 error TEXT,
+                error_kind TEXT,
+                attempts INTEGER,
+                attempt_errors TEXT,
+                tags TEXT,
+                metadata TEXT,
+                trial_stats TEXT,
+                aggregate_score REAL,
+                passed INTEGER,
                 FOREIGN KEY(eval_id) REFERENCES evals(id)
             )",
             [],
         )?;
+        // Databases created before these columns existed
+        add_column_if_missing(&conn, "evals", "status", "TEXT")?;
+        add_column_if_missing(&conn, "results", "case_index", "INTEGER")?;
+        add_column_if_missing(&conn, "results", "error_kind", "TEXT")?;
+        add_column_if_missing(&conn, "results", "attempts", "INTEGER")?;
+        add_column_if_missing(&conn, "results", "attempt_errors", "TEXT")?;
+        add_column_if_missing(&conn, "results", "tags", "TEXT")?;
+        add_column_if_missing(&conn, "results", "metadata", "TEXT")?;
+        add_column_if_missing(&conn, "results", "trial_stats", "TEXT")?;
+        add_column_if_missing(&conn, "results", "aggregate_score", "REAL")?;
+        add_column_if_missing(&conn, "results", "passed", "INTEGER")?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS scores (
@@ -120,6 +142,13 @@ impl Store {
         Ok(conn.last_insert_rowid())
     }
 
+    /// Replace a run's metadata (e.g. to add stats only known once it finishes)
+    pub fn update_run_metadata(&self, run_id: i64, metadata: &serde_json::Value) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE runs SET metadata = ?1 WHERE id = ?2", params![metadata.to_string(), run_id])?;
+        Ok(())
+    }
+
     /// Save a full evaluation result into the database
     pub fn save_eval(&self, run_id: i64, name: &str, result: &EvalResult) -> Result<i64> {
         let mut conn = self.conn.lock().unwrap();
@@ -127,65 +156,318 @@ impl Store {
 
         // 1. Create Eval
         tx.execute(
-            "INSERT INTO evals (run_id, name, summary) VALUES (?1, ?2, ?3)",
+            "INSERT INTO evals (run_id, name, summary, status) VALUES (?1, ?2, ?3, ?4)",
             params![
                 run_id, 
                 name, 
-                serde_json::to_string(&result.summary).ok()
+                serde_json::to_string(&result.summary).ok(),
+                result.status.as_str()
             ],
         )?;
         let eval_id = tx.last_insert_rowid();
 
         // 2. Save Results
         for case in &result.cases {
-            tx.execute(
-                "INSERT INTO results (eval_id, case_id, input, output, expected, error) 
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![
-                    eval_id,
-                    case.case.id,
-                    case.case.input.to_string(),
-                    case.output.to_string(),
-                    case.case.expected.to_string(),
-                    case.error
-                ],
-            )?;
-            let result_id = tx.last_insert_rowid();
-
-            // 3. Save Scores
-            for score in &case.scores {
-                tx.execute(
-                    "INSERT INTO scores (result_id, name, value, passed, details) 
-                     VALUES (?1, ?2, ?3, ?4, ?5)",
-                    params![
-                        result_id,
-                        score.name,
-                        score.value,
-                        score.passed,
-                        score.details.as_ref().map(|d| d.to_string())
-                    ],
-                )?;
-            }
-
-            // 4. Save Traces
-            for trace in &case.traces {
-                tx.execute(
-                    "INSERT INTO traces (result_id, model, duration_ms, input, output, tokens_in, tokens_out) 
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                    params![
-                        result_id,
-                        trace.model,
-                        trace.duration_ms,
-                        trace.input.to_string(),
-                        trace.output.to_string(),
-                        trace.usage.as_ref().map(|u| u.input_tokens),
-                        trace.usage.as_ref().map(|u| u.output_tokens),
-                    ],
-                )?;
-            }
+            insert_case(&tx, eval_id, case)?;
         }
 
         tx.commit()?;
         Ok(eval_id)
     }
+
+    /// Create an eval row to be filled case by case with `save_case`
+    pub fn begin_eval(&self, run_id: i64, name: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO evals (run_id, name, status) VALUES (?1, ?2, 'running')",
+            params![run_id, name],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Save one case result, replacing an earlier result for the same case (same
+    /// dataset index and id, as when resuming). Cases that merely share an id,
+    /// such as duplicate dataset rows, are all kept.
+    pub fn save_case(&self, eval_id: i64, case: &CaseResult) -> Result<i64> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let key = params![eval_id, case.index as i64, case.case.id];
+        let stale = "SELECT id FROM results WHERE eval_id = ?1 AND case_index = ?2 AND case_id IS ?3";
+        tx.execute(&format!("DELETE FROM scores WHERE result_id IN ({})", stale), key)?;
+        tx.execute(&format!("DELETE FROM traces WHERE result_id IN ({})", stale), key)?;
+        tx.execute(&format!("DELETE FROM results WHERE id IN ({})", stale), key)?;
+        let result_id = insert_case(&tx, eval_id, case)?;
+        tx.commit()?;
+        Ok(result_id)
+    }
+
+    /// Record the final summary and status of an eval started with `begin_eval`
+    pub fn finish_eval(&self, eval_id: i64, summary: &EvalSummary, status: RunStatus) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE evals SET summary = ?1, status = ?2 WHERE id = ?3",
+            params![serde_json::to_string(summary).ok(), status.as_str(), eval_id],
+        )?;
+        Ok(())
+    }
+
+    /// The most recent eval called `name`, optionally within one run, as (run_id, eval_id)
+    pub fn find_eval(&self, name: &str, run_id: Option<i64>) -> Result<Option<(i64, i64)>> {
+        let conn = self.conn.lock().unwrap();
+        let found = conn
+            .query_row(
+                "SELECT run_id, id FROM evals WHERE name = ?1 AND (?2 IS NULL OR run_id = ?2) ORDER BY id DESC LIMIT 1",
+                params![name, run_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(found)
+    }
+
+    /// Ids of the cases in an eval whose result has no error
+    pub fn completed_case_ids(&self, eval_id: i64) -> Result<HashSet<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT case_id FROM results WHERE eval_id = ?1 AND case_id IS NOT NULL AND error IS NULL")?;
+        let ids = stmt
+            .query_map(params![eval_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(ids)
+    }
+
+    /// Load the case results of an eval in dataset order, with their scores (traces are not restored)
+    pub fn load_results(&self, eval_id: i64) -> Result<Vec<CaseResult>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, case_index, case_id, input, output, expected, error, error_kind, attempts, attempt_errors, tags, metadata, trial_stats, aggregate_score, passed
+             FROM results WHERE eval_id = ?1 ORDER BY case_index, id",
+        )?;
+        let rows = stmt
+            .query_map(params![eval_id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    StoredCase {
+                        index: row.get::<_, Option<i64>>(1)?.unwrap_or(0) as usize,
+                        id: row.get(2)?,
+                        input: row.get(3)?,
+                        output: row.get(4)?,
+                        expected: row.get(5)?,
+                        error: row.get(6)?,
+                        error_kind: row.get(7)?,
+                        attempts: row.get::<_, Option<u32>>(8)?.unwrap_or(1),
+                        attempt_errors: row.get(9)?,
+                        tags: row.get(10)?,
+                        metadata: row.get(11)?,
+                        trial_stats: row.get(12)?,
+                        aggregate_score: row.get(13)?,
+                        passed: row.get(14)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut scores_stmt = conn.prepare("SELECT name, value, passed, details FROM scores WHERE result_id = ?1 ORDER BY id")?;
+        rows.into_iter()
+            .map(|(result_id, stored)| {
+                let scores = scores_stmt
+                    .query_map(params![result_id], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?, row.get::<_, Option<String>>(3)?))
+                    })?
+                    .map(|row| {
+                        let (name, value, passed, details) = row?;
+                        let details = details.map(|d| serde_json::from_str(&d)).transpose()?;
+                        Ok(Score { name, value, passed, details })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                stored.into_case_result(scores)
+            })
+            .collect()
+    }
+}
+
+/// A `results` row as stored, before parsing its JSON columns.
+struct StoredCase {
+    index: usize,
+    id: Option<String>,
+    input: String,
+    output: String,
+    expected: String,
+    error: Option<String>,
+    error_kind: Option<String>,
+    attempts: u32,
+    attempt_errors: Option<String>,
+    tags: Option<String>,
+    metadata: Option<String>,
+    trial_stats: Option<String>,
+    aggregate_score: Option<f64>,
+    passed: Option<bool>,
+}
+
+impl StoredCase {
+    fn into_case_result(self, scores: Vec<Score>) -> Result<CaseResult> {
+        let json = |s: Option<String>| s.map(|s| serde_json::from_str::<serde_json::Value>(&s)).transpose();
+        let mut case = TestCase::new(serde_json::from_str(&self.input)?, serde_json::from_str(&self.expected)?)
+            .metadata(json(self.metadata)?.unwrap_or_default());
+        case.id = self.id;
+        if let Some(tags) = json(self.tags)? {
+            case.tags = serde_json::from_value(tags)?;
+        }
+        Ok(CaseResult {
+            index: self.index,
+            case,
+            output: serde_json::from_str(&self.output)?,
+            error: self.error,
+            error_kind: self.error_kind.map(|k| serde_json::from_value(serde_json::Value::String(k))).transpose()?,
+            attempts: self.attempts,
+            attempt_errors: json(self.attempt_errors)?.map(serde_json::from_value).transpose()?.unwrap_or_default(),
+            scores,
+            aggregate: self
+                .aggregate_score
+                .zip(self.passed)
+                .map(|(value, passed)| CaseScore { value, passed }),
+            traces: Vec::new(),
+            trials: Vec::new(),
+            trial_stats: json(self.trial_stats)?.map(serde_json::from_value).transpose()?,
+        })
+    }
+}
+
+fn insert_case(conn: &Connection, eval_id: i64, case: &CaseResult) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO results (eval_id, case_index, case_id, input, output, expected, error, error_kind, attempts, attempt_errors, tags, metadata, trial_stats, aggregate_score, passed) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            eval_id,
+            case.index as i64,
+            case.case.id,
+            case.case.input.to_string(),
+            case.output.to_string(),
+            case.case.expected.to_string(),
+            case.error,
+            case.error_kind.map(|k| k.as_str()),
+            case.attempts,
+            (!case.attempt_errors.is_empty()).then(|| serde_json::json!(case.attempt_errors).to_string()),
+            (!case.case.tags.is_empty()).then(|| serde_json::json!(case.case.tags).to_string()),
+            (!case.case.metadata.is_null()).then(|| case.case.metadata.to_string()),
+            case.trial_stats.as_ref().and_then(|s| serde_json::to_string(s).ok()),
+            case.aggregate.map(|a| a.value),
+            case.aggregate.map(|a| a.passed)
+        ],
+    )?;
+    let result_id = conn.last_insert_rowid();
+
+    // Scores
+    for score in &case.scores {
+        conn.execute(
+            "INSERT INTO scores (result_id, name, value, passed, details) 
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                result_id,
+                score.name,
+                score.value,
+                score.passed,
+                score.details.as_ref().map(|d| d.to_string())
+            ],
+        )?;
+    }
+
+    // Traces, including those of every trial
+    for trace in case.all_traces() {
+        conn.execute(
+            "INSERT INTO traces (result_id, model, duration_ms, input, output, tokens_in, tokens_out) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                result_id,
+                trace.model,
+                trace.duration_ms,
+                trace.input.to_string(),
+                trace.output.to_string(),
+                trace.usage.as_ref().map(|u| u.input_tokens),
+                trace.usage.as_ref().map(|u| u.output_tokens),
+            ],
+        )?;
+    }
+
+    Ok(result_id)
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, ty: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, ty), [])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evalcraft_types::{ErrorKind, Trial};
+    use serde_json::json;
+
+    fn result(index: usize, id: &str, error: Option<&str>) -> CaseResult {
+        let case = TestCase::with_id(id, json!(index), json!(index)).tags(["math"]);
+        let scores = match error {
+            Some(_) => Vec::new(),
+            None => vec![Score { name: "exact".to_string(), value: 1.0, passed: true, details: None }],
+        };
+        CaseResult::from_trials(
+            index,
+            case,
+            vec![Trial {
+                output: if error.is_some() { json!(null) } else { json!(index) },
+                error: error.map(str::to_string),
+                error_kind: error.map(|_| ErrorKind::Task),
+                attempts: 1,
+                attempt_errors: Vec::new(),
+                scores,
+                aggregate: error.is_none().then_some(CaseScore { value: 0.75, passed: true }),
+                traces: Vec::new(),
+            }],
+        )
+    }
+
+    #[test]
+    fn test_incremental_eval_and_resume_queries() {
+        let store = Store::open(":memory:").unwrap();
+        let run_id = store.create_run(None).unwrap();
+        let eval_id = store.begin_eval(run_id, "qa").unwrap();
+        store.save_case(eval_id, &result(1, "b", Some("503"))).unwrap();
+        store.save_case(eval_id, &result(0, "a", None)).unwrap();
+
+        assert_eq!(store.find_eval("qa", None).unwrap(), Some((run_id, eval_id)));
+        assert_eq!(store.find_eval("qa", Some(run_id + 1)).unwrap(), None);
+        assert_eq!(store.completed_case_ids(eval_id).unwrap(), HashSet::from(["a".to_string()]));
+
+        // Re-running "b" replaces its errored result
+        store.save_case(eval_id, &result(1, "b", None)).unwrap();
+        let loaded = store.load_results(eval_id).unwrap();
+        assert_eq!(loaded.iter().map(|cr| cr.case.id.as_deref()).collect::<Vec<_>>(), vec![Some("a"), Some("b")]);
+        assert!(loaded[1].error.is_none());
+        assert_eq!(loaded[1].scores.len(), 1);
+        assert_eq!(loaded[1].aggregate, Some(CaseScore { value: 0.75, passed: true }));
+        assert_eq!(loaded[0].case.tags, vec!["math"]);
+
+        let summary = EvalResult::summarize(&loaded);
+        store.finish_eval(eval_id, &summary, RunStatus::Completed).unwrap();
+        assert_eq!(summary.passed, 2);
+    }
+
+    #[test]
+    fn test_cases_sharing_an_id_are_all_kept() {
+        let store = Store::open(":memory:").unwrap();
+        let run_id = store.create_run(None).unwrap();
+        let eval_id = store.begin_eval(run_id, "qa").unwrap();
+        store.save_case(eval_id, &result(0, "dup", None)).unwrap();
+        store.save_case(eval_id, &result(1, "dup", Some("503"))).unwrap();
+
+        let loaded = store.load_results(eval_id).unwrap();
+        assert_eq!(loaded.iter().map(|cr| cr.index).collect::<Vec<_>>(), vec![0, 1]);
+        assert!(loaded[0].error.is_none());
+        assert_eq!(loaded[0].scores.len(), 1);
+        assert_eq!(loaded[1].error.as_deref(), Some("503"));
+    }
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::time::SystemTime;
 use tabled::Tabled;
 
@@ -143,16 +144,52 @@ pub struct TestCase {
 	pub id: Option<String>,
 	pub input: Value,
 	pub expected: Value,
+	/// Categories used for per-tag breakdowns (e.g. "math", "geography").
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<String>,
+	/// Free-form per-case data that isn't part of the input.
+	#[serde(default, skip_serializing_if = "Value::is_null")]
+	pub metadata: Value,
 }
 
 impl TestCase {
 	pub fn new(input: Value, expected: Value) -> Self {
-		Self { id: None, input, expected }
+		Self { id: None, input, expected, tags: Vec::new(), metadata: Value::Null }
 	}
 
 	pub fn with_id(id: impl Into<String>, input: Value, expected: Value) -> Self {
-		Self { id: Some(id.into()), input, expected }
+		Self { id: Some(id.into()), ..Self::new(input, expected) }
 	}
+
+	pub fn tags<I, S>(mut self, tags: I) -> Self
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		self.tags = tags.into_iter().map(Into::into).collect();
+		self
+	}
+
+	pub fn metadata(mut self, metadata: Value) -> Self {
+		self.metadata = metadata;
+		self
+	}
+
+	/// A stable id derived from the case content (`input` and `expected`), used for
+	/// cases that don't carry one so they can be tracked across runs.
+	pub fn content_id(&self) -> String {
+		let content = format!("{}\n{}", self.input, self.expected);
+		format!("case-{:016x}", stable_hash(content.as_bytes()))
+	}
+}
+
+/// FNV-1a hash of `bytes`: tiny and stable across platforms and releases, unlike
+/// `DefaultHasher`, so it is safe for ids, shards and file names that must not
+/// change between runs.
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+	bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+		(hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+	})
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -164,19 +201,323 @@ pub struct Score {
 	pub details: Option<Value>,
 }
 
+/// A case's (or trial's) scores combined under the eval's pass policy.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CaseScore {
+	/// Weighted mean of the required scores.
+	pub value: f64,
+	pub passed: bool,
+}
+
+/// Why a case produced no output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+	/// `Task::run` returned an error.
+	Task,
+	/// The case timeout or the run deadline elapsed before the task finished.
+	Timeout,
+	/// The run was cancelled before the case started.
+	Skipped,
+}
+
+impl ErrorKind {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ErrorKind::Task => "task",
+			ErrorKind::Timeout => "timeout",
+			ErrorKind::Skipped => "skipped",
+		}
+	}
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CaseResult {
+	/// Position of the case in the data source, before any selection.
+	#[serde(default)]
+	pub index: usize,
 	pub case: TestCase,
 	pub output: Value,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub error: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error_kind: Option<ErrorKind>,
+	/// How many times the task was run (more than 1 when retried).
+	#[serde(default = "default_attempts")]
+	pub attempts: u32,
+	/// Errors of every failed attempt, oldest first.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub attempt_errors: Vec<String>,
 	pub scores: Vec<Score>,
+	/// The scores combined under the eval's pass policy; `None` when the case wasn't scored.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub aggregate: Option<CaseScore>,
 	#[serde(skip_serializing_if = "Vec::is_empty", default)]
 	pub traces: Vec<Trace>,
+	/// Every trial, when the eval runs each case more than once.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub trials: Vec<Trial>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub trial_stats: Option<TrialStats>,
+}
+
+fn default_attempts() -> u32 {
+	1
+}
+
+impl CaseResult {
+	/// Build a result from the trials run for `case`. A single trial becomes the
+	/// result as is.
+	///
+	/// With more than one trial, every trial is kept in `trials` (with its own
+	/// traces) and aggregated in `trial_stats`. The top-level result then judges
+	/// the case on all trials: each score is the mean over the trials and passes
+	/// when most trials passed it, and the aggregate is the mean trial score,
+	/// passing when more than half of the trials passed. The output, error and
+	/// attempts are those of the first trial that succeeded (or the first trial
+	/// when all failed); top-level `traces` is left empty.
+	pub fn from_trials(index: usize, case: TestCase, mut trials: Vec<Trial>) -> Self {
+		if trials.len() == 1 {
+			let trial = trials.remove(0);
+			return CaseResult {
+				index,
+				case,
+				output: trial.output,
+				error: trial.error,
+				error_kind: trial.error_kind,
+				attempts: trial.attempts,
+				attempt_errors: trial.attempt_errors,
+				scores: trial.scores,
+				aggregate: trial.aggregate,
+				traces: trial.traces,
+				trials: Vec::new(),
+				trial_stats: None,
+			};
+		}
+
+		let stats = TrialStats::from_trials(&trials);
+		let scored = trials.iter().any(|t| t.aggregate.is_some() || !t.scores.is_empty());
+		let aggregate = scored.then(|| CaseScore { value: stats.mean_score, passed: stats.passed * 2 > stats.trials });
+		let scores = mean_scores(&trials);
+		let representative = trials.iter().find(|t| t.error.is_none()).or(trials.first()).cloned();
+		let (output, error, error_kind, attempts, attempt_errors) = match representative {
+			Some(t) => (t.output, t.error, t.error_kind, t.attempts, t.attempt_errors),
+			None => (Value::Null, None, None, 0, Vec::new()),
+		};
+		CaseResult {
+			index,
+			case,
+			output,
+			error,
+			error_kind,
+			attempts,
+			attempt_errors,
+			scores,
+			aggregate,
+			traces: Vec::new(),
+			trials,
+			trial_stats: Some(stats),
+		}
+	}
+
+	/// The case's traces followed by those of each trial.
+	pub fn all_traces(&self) -> impl Iterator<Item = &Trace> {
+		self.traces.iter().chain(self.trials.iter().flat_map(|t| &t.traces))
+	}
+
+	/// A result for a case that was never run, e.g. because the run was cancelled.
+	pub fn skipped(index: usize, case: TestCase, reason: impl Into<String>) -> Self {
+		Self::from_trials(
+			index,
+			case,
+			vec![Trial {
+				output: Value::Null,
+				error: Some(reason.into()),
+				error_kind: Some(ErrorKind::Skipped),
+				attempts: 0,
+				attempt_errors: Vec::new(),
+				scores: Vec::new(),
+				aggregate: None,
+				traces: Vec::new(),
+			}],
+		)
+	}
+
+	/// Whether the case passed under the eval's pass policy. Results without an
+	/// aggregate (e.g. from older runs) pass when every score passed.
+	pub fn passed(&self) -> bool {
+		passed(self.aggregate, &self.scores)
+	}
+
+	/// The aggregate score, or the mean of the scores for results without one.
+	pub fn aggregate_score(&self) -> Option<f64> {
+		aggregate_score(self.aggregate, &self.scores)
+	}
+
+	pub fn timed_out(&self) -> bool {
+		self.error_kind == Some(ErrorKind::Timeout)
+	}
+
+	pub fn was_skipped(&self) -> bool {
+		self.error_kind == Some(ErrorKind::Skipped)
+	}
+}
+
+/// One run of the task (plus scoring) for a case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trial {
+	pub output: Value,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error_kind: Option<ErrorKind>,
+	#[serde(default = "default_attempts")]
+	pub attempts: u32,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub attempt_errors: Vec<String>,
+	pub scores: Vec<Score>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub aggregate: Option<CaseScore>,
+	#[serde(skip_serializing_if = "Vec::is_empty", default)]
+	pub traces: Vec<Trace>,
+}
+
+impl Trial {
+	/// Same rule as for cases (see `CaseResult::passed`).
+	pub fn passed(&self) -> bool {
+		passed(self.aggregate, &self.scores)
+	}
+
+	fn mean_score(&self) -> f64 {
+		aggregate_score(self.aggregate, &self.scores).unwrap_or(0.0)
+	}
+}
+
+/// Each scorer's value averaged over the trials that have it, passing when it
+/// passed in more than half of them. Scorers keep the order they first appear in.
+fn mean_scores(trials: &[Trial]) -> Vec<Score> {
+	let mut names: Vec<&str> = Vec::new();
+	for score in trials.iter().flat_map(|t| &t.scores) {
+		if !names.contains(&score.name.as_str()) {
+			names.push(&score.name);
+		}
+	}
+	names
+		.into_iter()
+		.map(|name| {
+			let matching: Vec<&Score> = trials.iter().flat_map(|t| &t.scores).filter(|s| s.name == name).collect();
+			let n = matching.len();
+			Score {
+				name: name.to_string(),
+				value: matching.iter().map(|s| s.value).sum::<f64>() / n as f64,
+				passed: matching.iter().filter(|s| s.passed).count() * 2 > n,
+				details: None,
+			}
+		})
+		.collect()
+}
+
+fn passed(aggregate: Option<CaseScore>, scores: &[Score]) -> bool {
+	match aggregate {
+		Some(aggregate) => aggregate.passed,
+		None => !scores.is_empty() && scores.iter().all(|s| s.passed),
+	}
+}
+
+fn aggregate_score(aggregate: Option<CaseScore>, scores: &[Score]) -> Option<f64> {
+	match aggregate {
+		Some(aggregate) => Some(aggregate.value),
+		None if scores.is_empty() => None,
+		None => Some(scores.iter().map(|s| s.value).sum::<f64>() / scores.len() as f64),
+	}
+}
+
+/// Aggregates over the trials of one case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrialStats {
+	pub trials: usize,
+	pub passed: usize,
+	/// Mean of each trial's aggregate score.
+	pub mean_score: f64,
+	/// pass@k for k = 1..=trials: the chance that at least one of k samples passes
+	/// (unbiased estimator).
+	pub pass_at_k: BTreeMap<usize, f64>,
+	/// pass^k for k = 1..=trials: the chance that all of k samples pass.
+	pub pass_all_k: BTreeMap<usize, f64>,
+	/// Some trials passed and some didn't.
+	pub flaky: bool,
+}
+
+impl TrialStats {
+	pub fn from_trials(trials: &[Trial]) -> Self {
+		let n = trials.len();
+		let c = trials.iter().filter(|t| t.passed()).count();
+		let mean_score = if n == 0 { 0.0 } else { trials.iter().map(Trial::mean_score).sum::<f64>() / n as f64 };
+		TrialStats {
+			trials: n,
+			passed: c,
+			mean_score,
+			pass_at_k: (1..=n).map(|k| (k, pass_at_k(n, c, k))).collect(),
+			pass_all_k: (1..=n).map(|k| (k, pass_all_k(n, c, k))).collect(),
+			flaky: c > 0 && c < n,
+		}
+	}
+}
+
+/// Unbiased pass@k estimate from `n` samples of which `c` passed: 1 - C(n-c, k) / C(n, k).
+pub fn pass_at_k(n: usize, c: usize, k: usize) -> f64 {
+	if k == 0 || k > n {
+		return 0.0;
+	}
+	if n - c < k {
+		return 1.0;
+	}
+	1.0 - ((n - c + 1)..=n).map(|i| 1.0 - k as f64 / i as f64).product::<f64>()
+}
+
+/// pass^k estimate from `n` samples of which `c` passed: C(c, k) / C(n, k).
+pub fn pass_all_k(n: usize, c: usize, k: usize) -> f64 {
+	if k == 0 || k > n {
+		return 0.0;
+	}
+	(0..k).map(|i| c.saturating_sub(i) as f64 / (n - i) as f64).product()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvalSummary {
+	pub total: usize,
+	pub passed: usize,
+	pub pass_rate: f64,
+	/// Mean of the cases' aggregate scores, over the cases that were scored.
+	pub avg_score: f64,
+	/// Breakdown per `TestCase` tag; cases with several tags count towards each.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub by_tag: BTreeMap<String, TagSummary>,
+	/// Dataset-level trial statistics, when cases were run more than once.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub trials: Option<TrialSummary>,
+	/// Cases not run because the run was cancelled; they don't count towards `total`.
+	#[serde(default, skip_serializing_if = "is_zero")]
+	pub skipped: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+	*n == 0
+}
+
+/// `TrialStats` averaged over all cases that ran several trials.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrialSummary {
+	pub trials_per_case: usize,
+	pub mean_score: f64,
+	pub pass_at_k: BTreeMap<usize, f64>,
+	pub pass_all_k: BTreeMap<usize, f64>,
+	/// Number of cases with mixed trial outcomes.
+	pub flaky: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSummary {
 	pub total: usize,
 	pub passed: usize,
 	pub pass_rate: f64,
@@ -187,13 +528,166 @@ pub struct EvalSummary {
 pub struct EvalResult {
 	pub cases: Vec<CaseResult>,
 	pub summary: EvalSummary,
+	#[serde(default)]
+	pub status: RunStatus,
+	/// Why the run stopped early, when `status` is `RunStatus::Failed`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// How the in-flight limit changed over the run (adaptive concurrency only).
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub concurrency: Vec<ConcurrencySample>,
+}
+
+/// Whether a run went through its whole dataset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+	#[default]
+	Completed,
+	/// Cancelled part-way; cases that hadn't started are marked skipped.
+	Interrupted,
+	/// The data source failed part-way; only the cases read before the error ran.
+	Failed,
+}
+
+impl RunStatus {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			RunStatus::Completed => "completed",
+			RunStatus::Interrupted => "interrupted",
+			RunStatus::Failed => "failed",
+		}
+	}
+}
+
+/// The concurrency limit chosen at a point in the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcurrencySample {
+	/// Milliseconds since the run started.
+	pub elapsed_ms: u64,
+	pub limit: usize,
+}
+
+/// Folds `CaseResult`s into an `EvalSummary` one at a time, without keeping them around.
+#[derive(Debug, Clone, Default)]
+pub struct SummaryAccumulator {
+	overall: Tally,
+	by_tag: BTreeMap<String, Tally>,
+	trials: Option<TrialTally>,
+	skipped: usize,
+}
+
+impl SummaryAccumulator {
+	pub fn add(&mut self, cr: &CaseResult) {
+		if cr.was_skipped() {
+			self.skipped += 1;
+			return;
+		}
+		self.overall.add(cr);
+		for tag in &cr.case.tags {
+			self.by_tag.entry(tag.clone()).or_default().add(cr);
+		}
+		if let Some(stats) = &cr.trial_stats {
+			self.trials.get_or_insert_with(TrialTally::default).add(stats);
+		}
+	}
+
+	pub fn finish(&self) -> EvalSummary {
+		let (total, passed, pass_rate, avg_score) = self.overall.rates();
+		let by_tag = self
+			.by_tag
+			.iter()
+			.map(|(tag, tally)| {
+				let (total, passed, pass_rate, avg_score) = tally.rates();
+				(tag.clone(), TagSummary { total, passed, pass_rate, avg_score })
+			})
+			.collect();
+
+		let trials = self.trials.as_ref().map(TrialTally::finish);
+		EvalSummary { total, passed, pass_rate, avg_score, by_tag, trials, skipped: self.skipped }
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+struct TrialTally {
+	cases: usize,
+	trials_per_case: usize,
+	score_sum: f64,
+	pass_at_k: BTreeMap<usize, f64>,
+	pass_all_k: BTreeMap<usize, f64>,
+	flaky: usize,
+}
+
+impl TrialTally {
+	fn add(&mut self, stats: &TrialStats) {
+		self.cases += 1;
+		self.trials_per_case = self.trials_per_case.max(stats.trials);
+		self.score_sum += stats.mean_score;
+		for (k, p) in &stats.pass_at_k {
+			*self.pass_at_k.entry(*k).or_default() += p;
+		}
+		for (k, p) in &stats.pass_all_k {
+			*self.pass_all_k.entry(*k).or_default() += p;
+		}
+		if stats.flaky {
+			self.flaky += 1;
+		}
+	}
+
+	fn finish(&self) -> TrialSummary {
+		let n = self.cases.max(1) as f64;
+		TrialSummary {
+			trials_per_case: self.trials_per_case,
+			mean_score: self.score_sum / n,
+			pass_at_k: self.pass_at_k.iter().map(|(k, sum)| (*k, sum / n)).collect(),
+			pass_all_k: self.pass_all_k.iter().map(|(k, sum)| (*k, sum / n)).collect(),
+			flaky: self.flaky,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+struct Tally {
+	total: usize,
+	passed: usize,
+	score_sum: f64,
+	score_count: usize,
+}
+
+impl Tally {
+	fn add(&mut self, cr: &CaseResult) {
+		self.total += 1;
+		if cr.passed() {
+			self.passed += 1;
+		}
+		if let Some(score) = cr.aggregate_score() {
+			self.score_sum += score;
+			self.score_count += 1;
+		}
+	}
+
+	/// (total, passed, pass_rate, avg_score)
+	fn rates(&self) -> (usize, usize, f64, f64) {
+		let pass_rate = if self.total == 0 { 0.0 } else { self.passed as f64 / self.total as f64 };
+		let avg_score = if self.score_count == 0 { 0.0 } else { self.score_sum / self.score_count as f64 };
+		(self.total, self.passed, pass_rate, avg_score)
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tabled)]
+struct TagRow {
+	tag: String,
+	total: usize,
+	passed: usize,
+	pass_rate: String,
+	avg_score: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Tabled)]
 struct SummaryRow {
 	id: String,
 	passed: String,
-	avg_score: f64,
+	score: f64,
 	input: String,
 	output: String,
 	expected: String,
@@ -201,44 +695,31 @@ struct SummaryRow {
 
 impl EvalResult {
 	pub fn summarize(cases: &[CaseResult]) -> EvalSummary {
-		let total = cases.len();
-		let mut passed = 0usize;
-		let mut score_sum = 0.0f64;
-		let mut score_count = 0usize;
-		
+		let mut acc = SummaryAccumulator::default();
 		for cr in cases {
-			let all_passed = !cr.scores.is_empty() && cr.scores.iter().all(|s| s.passed);
-			if all_passed {
-				passed += 1;
-			}
-			for s in &cr.scores {
-				score_sum += s.value;
-				score_count += 1;
-			}
+			acc.add(cr);
 		}
-
-		let pass_rate = if total == 0 { 0.0 } else { passed as f64 / total as f64 };
-		let avg_score = if score_count == 0 { 0.0 } else { score_sum / score_count as f64 };
-
-		EvalSummary { total, passed, pass_rate, avg_score }
+		acc.finish()
 	}
 
 	pub fn summary_table(&self) -> String {
         use tabled::Table;
 		let rows: Vec<SummaryRow> = self.cases.iter().map(|cr| {
 			let id = cr.case.id.clone().unwrap_or_else(|| "-".to_string());
-			let passed = if !cr.scores.is_empty() && cr.scores.iter().all(|s| s.passed) { "✓" } else { " " };
-			let avg = if cr.scores.is_empty() {
-				0.0
+			let passed = if cr.timed_out() {
+				"⏱"
+			} else if cr.was_skipped() {
+				"-"
+			} else if cr.passed() {
+				"✓"
 			} else {
-				let sum: f64 = cr.scores.iter().map(|s| s.value).sum();
-				sum / (cr.scores.len() as f64)
+				" "
 			};
-			
+
 			SummaryRow {
 				id,
 				passed: passed.to_string(),
-				avg_score: avg,
+				score: cr.aggregate_score().unwrap_or(0.0),
 				input: truncate(value_preview(&cr.case.input), 64),
 				output: truncate(value_preview(&cr.output), 64),
 				expected: truncate(value_preview(&cr.case.expected), 64),
@@ -256,7 +737,43 @@ impl EvalResult {
 			self.summary.avg_score
 		);
 
-		format!("{}\n\n{}\n", table_str, summary_text)
+		let summary_text = match &self.summary.trials {
+			Some(t) => {
+				let k = t.trials_per_case;
+				format!(
+					"{}\nTrials: {} per case  pass@1: {:.1}%  pass@{}: {:.1}%  pass^{}: {:.1}%  Flaky: {}",
+					summary_text,
+					k,
+					t.pass_at_k.get(&1).copied().unwrap_or(0.0) * 100.0,
+					k,
+					t.pass_at_k.get(&k).copied().unwrap_or(0.0) * 100.0,
+					k,
+					t.pass_all_k.get(&k).copied().unwrap_or(0.0) * 100.0,
+					t.flaky
+				)
+			}
+			None => summary_text,
+		};
+
+		let summary_text = match self.status {
+			RunStatus::Interrupted => format!("{}\nInterrupted: {} case(s) skipped", summary_text, self.summary.skipped),
+			RunStatus::Failed => format!("{}\nFailed: {}", summary_text, self.error.as_deref().unwrap_or("unknown error")),
+			RunStatus::Completed => summary_text,
+		};
+
+		if self.summary.by_tag.is_empty() {
+			return format!("{}\n\n{}\n", table_str, summary_text);
+		}
+
+		let tag_rows: Vec<TagRow> = self.summary.by_tag.iter().map(|(tag, t)| TagRow {
+			tag: tag.clone(),
+			total: t.total,
+			passed: t.passed,
+			pass_rate: format!("{:.1}%", t.pass_rate * 100.0),
+			avg_score: format!("{:.3}", t.avg_score),
+		}).collect();
+
+		format!("{}\n\n{}\n\nBy tag:\n{}\n", table_str, summary_text, Table::new(tag_rows))
 	}
 }
 
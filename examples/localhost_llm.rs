@@ -39,10 +39,10 @@ fn make_embed_fn() -> Arc<dyn for<'a> Fn(&'a str) -> EmbedFuture<'a> + Send + Sy
             // Support shape: [{"index":0,"embedding":[[...]]}]
             let arr = v
                 .as_array()
-                .and_then(|outer| outer.get(0))
+                .and_then(|outer| outer.first())
                 .and_then(|obj| obj.get("embedding"))
                 .and_then(|emb| emb.as_array())
-                .and_then(|emb_outer| emb_outer.get(0))
+                .and_then(|emb_outer| emb_outer.first())
                 .and_then(|inner| inner.as_array())
                 .ok_or_else(|| anyhow::anyhow!("unexpected embedding response shape"))?;
 
@@ -57,17 +57,13 @@ fn make_embed_fn() -> Arc<dyn for<'a> Fn(&'a str) -> EmbedFuture<'a> + Send + Sy
 }
 
 async fn run_localhost_llm() -> anyhow::Result<()> {
-    let task = from_async_fn(|input| {
-        let input = input.clone();
-        let model = "Qwen3-0.5b";
-        async move {
-            let prompt = input.as_str().unwrap_or_default();
-            let system_prompt =
-                "You are a helpful assistant. try to reply to answers as concise as possible, 1 word is enough.";
-            let response = call_oai_compat(system_prompt, prompt, model).await?;
-            Ok(json!(response))
-        }
-    });
+    let task = Arc::new(
+        ChatCompletionTask::new("http://localhost:8080", "Qwen3-0.5b")
+            .system_prompt(
+                "You are a helpful assistant. try to reply to answers as concise as possible, 1 word is enough.",
+            )
+            .temperature(0.0),
+    );
 
     let data = Arc::new(JsonlDataSource::new("examples/openai_tests.jsonl"));
 
@@ -116,25 +112,3 @@ async fn run_localhost_llm() -> anyhow::Result<()> {
 
     Ok(())
 }
-
-async fn call_oai_compat(system_prompt: &str, prompt: &str, model: &str) -> anyhow::Result<String> {
-    let client = Client::new();
-
-    let response = client
-        .post("http://localhost:8080/chat/completions")
-        .json(&json!({
-            "model": model,
-            "messages": [{"role": "system", "content": system_prompt}, {"role": "user", "content": prompt}],
-            "temperature": 0.0
-        }))
-        .send()
-        .await?;
-    let json: serde_json::Value = response.json().await?;
-
-    let content = json["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or_default()
-        .to_string();
-
-    Ok(content)
-}
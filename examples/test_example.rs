@@ -4,8 +4,11 @@
 //
 // This shows how to write evaluations as regular Rust tests.
 
+#[cfg(test)]
 use std::sync::Arc;
+#[cfg(test)]
 use evalcraft_core::*;
+#[cfg(test)]
 use serde_json::json;
 
 // This function is needed for the example to be compilable as a binary (cargo run --example test_example)